@@ -5,7 +5,7 @@ fn main() {
     // Generate mcbor bindings
     let opts = Options {
         language: Language::C,
-        prefix: None,
+        ..Options::default()
     };
     let root = PathBuf::from(env!("CARGO_MANIFEST_DIR"));
     let cddl = fs::read_to_string(root.parent().unwrap().join("data.cddl")).unwrap();
@@ -1,10 +1,15 @@
 use crate::ivt::ConstrainedType;
-use crate::{LinkedArray, LinkedKeyVal, LinkedNode, Literal, ValidateError};
+use crate::validate::unaliased;
+use crate::{
+    Fields, LinkedAlias, LinkedArray, LinkedBits, LinkedChoice, LinkedDefault, LinkedDictionary,
+    LinkedEmbedded, LinkedKeyVal, LinkedNode, LinkedRange, LinkedRegexp, LinkedTagged, LinkedUnion,
+    LinkedVector, Literal, ValidateError,
+};
 use heck::{ToLowerCamelCase, ToShoutySnakeCase, ToSnakeCase, ToUpperCamelCase};
 use lazy_static::lazy_static;
 use serde::{Deserialize, Serialize};
-use serde_json::{from_value, to_value};
-use std::collections::{BTreeMap, HashMap};
+use serde_json::{from_value, json, to_value};
+use std::collections::{BTreeMap, HashMap, HashSet};
 use std::include_str;
 use std::{error, fmt};
 use tera::Context as TeraContext;
@@ -23,21 +28,43 @@ lazy_static! {
         tera.register_filter("field", filter_field);
         tera.register_filter("field_attr", filter_field_attr);
         tera.register_filter("field_default", filter_field_default);
+        tera.register_filter("default_value", filter_default_value);
+        tera.register_filter("ty", filter_ty);
+        tera.register_filter("with", filter_with);
+        tera.register_filter("enum_repr", filter_enum_repr);
+        tera.register_filter("literal_value", filter_literal_value);
+        tera.register_filter("cbor_encode", filter_cbor_encode);
+        tera.register_filter("cbor_decode", filter_cbor_decode);
+        tera.register_filter("cbor_len", filter_cbor_len);
+        tera.register_filter("union_choice", filter_union_choice);
+        tera.register_filter("decode_expr", filter_decode_expr);
+        tera.register_filter("validate", filter_validate);
+        tera.register_filter("cbor_init", filter_cbor_init);
+        tera.register_filter("cbor_array_len", filter_cbor_array_len);
+        tera.register_filter("cbor_array_min", filter_cbor_array_min);
+        tera.register_filter("cbor_map_len", filter_cbor_map_len);
+        tera.register_filter("cbor_map_encode", filter_cbor_map_encode);
+        tera.register_filter("cbor_map_slot", filter_cbor_map_slot);
+        tera.register_filter("cbor_map_keys", filter_cbor_map_keys);
+        tera.register_filter("cbor_map_decode", filter_cbor_map_decode);
+        tera.register_filter("cbor_map_take", filter_cbor_map_take);
+        tera.register_filter("cbor_map_entry_len", filter_cbor_map_entry_len);
+        tera.register_filter("struct_codec", filter_struct_codec);
         tera.register_filter("rename", filter_rename);
         tera.register_filter("literal", filter_literal);
         tera.register_filter("nodes", filter_nodes);
         tera.register_filter("fn_attr", filter_fn_attr);
         tera.register_filter("wasm_member", filter_wasm_member);
+        tera.register_filter("alias_member", filter_alias_member);
+        tera.register_filter("array_fields", filter_array_fields);
+        tera.register_filter("dictionary", filter_dictionary);
+        tera.register_filter("bits", filter_bits);
+        tera.register_filter("regexp", filter_regexp);
+        tera.register_filter("embedded", filter_embedded);
         tera
     };
 }
 
-macro_rules! field_arr {
-    ($key:expr, $ty:literal, $len:expr) => {
-        format!("{}: [{}; {}]", $key, $ty, $len)
-    };
-}
-
 /// Our error type
 #[derive(Debug)]
 pub enum RenderError {
@@ -82,23 +109,336 @@ pub enum Language {
     Typescript,
 }
 
+/// How a rule that names another type is rendered
+#[derive(Default, PartialEq, Debug, Clone, Serialize, Deserialize)]
+#[serde(rename_all = "lowercase")]
+pub enum Alias {
+    /// A type alias (IE: pub type IpAddr = [u8; 16])
+    #[default]
+    Typedef,
+    /// A struct of one field with impls of it's own (IE: pub struct IpAddr(pub [u8; 16]))
+    Newtype,
+}
+
 /// Options for modifying behavior of rendered code
+///
+/// New options may be added, and each takes a default that renders code the way it was rendered
+/// before the option existed. Construct Options with the rest of it's fields from the default, so
+/// adding an option does not break the build (IE: alias, which was added after prefix)
+///
+/// ```
+/// use minicbor_bindgen::{Language, Options};
+///
+/// let opts = Options {
+///     language: Language::Rust,
+///     ..Options::default()
+/// };
+/// ```
 #[derive(Default, PartialEq, Debug, Clone, Serialize, Deserialize)]
 pub struct Options {
     pub language: Language,
     pub prefix: Option<String>,
+    /// Defaults to a typedef, and may be missing from serialized options
+    #[serde(default)]
+    pub alias: Alias,
 }
 
 /// Main entry to this module
 pub(crate) fn gen_lib(cddl: BTreeMap<String, LinkedNode>, opts: &Options) -> RenderResult<String> {
+    if opts.language == Language::C && cddl.values().any(unconstrained) {
+        return Err(RenderError::Validate(
+            ValidateError::InvalidUnconstrainedPrimative,
+        ));
+    }
+    if opts.language == Language::C && cddl.values().any(recursive) {
+        return Err(RenderError::Validate(ValidateError::InvalidRecursion));
+    }
+    let map = HashMap::from([(
+        "options".to_string(),
+        to_value(opts).map_err(TeraError::from)?,
+    )]);
+    let cddl = cddl
+        .into_iter()
+        .filter(|(key, node)| !self_named(key, node, &map))
+        .map(|(key, node)| {
+            let node = map_aliases(node, &|alias| match (
+                self_named(&alias.name, &alias.ty, &map),
+                array_kind(&alias.ty),
+            ) {
+                (true, _) => *alias.ty,
+                // An array rule is rendered as a type of it's own, like a struct
+                (_, true) => LinkedNode::ForeignStruct(alias.name),
+                (_, _) => LinkedNode::Alias(alias),
+            });
+            (key, node)
+        });
+    // A newtype is a type of it's own, so a reference to an alias is encoded with the impls of
+    // it's newtype like a reference to a struct
+    let cddl = match opts.alias {
+        Alias::Typedef => cddl.collect::<BTreeMap<String, LinkedNode>>(),
+        Alias::Newtype => cddl
+            .map(|(key, node)| {
+                let node = map_aliases(node, &|alias| LinkedNode::ForeignStruct(alias.name));
+                (key, node)
+            })
+            .collect(),
+    };
+    let cddl = match opts.language {
+        Language::Rust => cddl,
+        _ => cddl
+            .into_iter()
+            .map(|(key, node)| (key, name_record_fields(node)))
+            .collect(),
+    };
     let mut ctx = TeraContext::new();
     ctx.insert("cddl", &cddl);
     ctx.insert("options", opts);
+    ctx.insert("helpers", &Vec::<&str>::new());
+    let lib = TEMPLATES.render("lib.rs.tmpl", &ctx)?;
+    ctx.insert("helpers", &used_helpers(&lib));
     TEMPLATES
         .render("lib.rs.tmpl", &ctx)
         .map_err(RenderError::from)
 }
 
+/// The helpers of the rendered code, the paths each of them provides, and the helpers each of
+/// them uses
+const HELPERS: &[(&str, &[&str], &[&str])] = &[
+    (
+        "decode_range",
+        &[
+            "decode_range",
+            "decode_range_opt",
+            "decode_range_except",
+            "decode_range_opt_except",
+        ],
+        &[],
+    ),
+    ("pattern", &["Pattern"], &[]),
+    (
+        "array",
+        &[
+            "ArrayCodec",
+            "ByteString",
+            "Entry",
+            "Entries",
+            "Tagged",
+            "tag_of",
+            "Optional",
+            "encode_items",
+            "cbor_len_items",
+            "decode_items",
+            "encode_vec",
+            "cbor_len_vec",
+            "decode_vec",
+            "encode_opt_vec",
+            "cbor_len_opt_vec",
+            "decode_opt_vec",
+            "MapEntries",
+        ],
+        &[],
+    ),
+    ("float16", &["float16", "Half"], &["array"]),
+    ("text", &["text", "TextString"], &["array"]),
+    ("any", &["AnyCbor"], &[]),
+    (
+        "serde_big_array",
+        &["ser_opt_big_array", "de_opt_big_array"],
+        &[],
+    ),
+    (
+        "serde_str_array",
+        &[
+            "ser_bytes_array_as_strs",
+            "de_strs_as_bytes_array",
+            "ser_str_keyed_map",
+            "de_str_keyed_map",
+        ],
+        &[],
+    ),
+];
+
+/// A helper is only rendered when the code of the schema refers to it
+fn used_helpers(lib: &str) -> Vec<&'static str> {
+    let paths: HashSet<&str> = lib
+        .split("__bindgen::")
+        .skip(1)
+        .filter_map(|path| {
+            path.split(|c: char| !c.is_alphanumeric() && c != '_')
+                .next()
+        })
+        .collect();
+    let mut helpers: Vec<&str> = HELPERS
+        .iter()
+        .filter(|(_, provides, _)| provides.iter().any(|path| paths.contains(path)))
+        .flat_map(|(helper, _, uses)| std::iter::once(*helper).chain(uses.iter().copied()))
+        .collect();
+    helpers.sort_unstable();
+    helpers.dedup();
+    helpers
+}
+
+/// The unnamed entries of a record are rendered as a tuple struct in Rust. Other languages name
+/// every field, so the fields are named after their position
+fn name_record_fields(node: LinkedNode) -> LinkedNode {
+    match node {
+        LinkedNode::Record(Fields { members }) => LinkedNode::Record(Fields {
+            members: members
+                .into_iter()
+                .map(|LinkedKeyVal(key, node, wire)| match key.parse::<usize>() {
+                    Ok(_) => LinkedKeyVal(format!("field-{}", key), node, wire),
+                    Err(_) => LinkedKeyVal(key, node, wire),
+                })
+                .collect(),
+        }),
+        node => node,
+    }
+}
+
+/// Replace every alias of a node, after the aliases of the type it names are replaced
+fn map_aliases<F>(node: LinkedNode, f: &F) -> LinkedNode
+where
+    F: Fn(LinkedAlias) -> LinkedNode,
+{
+    let fields = |Fields { members }| Fields {
+        members: members
+            .into_iter()
+            .map(|LinkedKeyVal(key, node, wire)| LinkedKeyVal(key, map_aliases(node, f), wire))
+            .collect(),
+    };
+    match node {
+        LinkedNode::Alias(LinkedAlias { name, ty }) => f(LinkedAlias {
+            name,
+            ty: Box::new(map_aliases(*ty, f)),
+        }),
+        LinkedNode::Optional(node) => LinkedNode::Optional(Box::new(map_aliases(*node, f))),
+        LinkedNode::Array(LinkedArray { ty, len }) => LinkedNode::Array(LinkedArray {
+            ty: Box::new(map_aliases(*ty, f)),
+            len,
+        }),
+        LinkedNode::Vector(LinkedVector { ty, min, max }) => LinkedNode::Vector(LinkedVector {
+            ty: Box::new(map_aliases(*ty, f)),
+            min,
+            max,
+        }),
+        LinkedNode::Tagged(LinkedTagged { ty, tag }) => LinkedNode::Tagged(LinkedTagged {
+            ty: Box::new(map_aliases(*ty, f)),
+            tag,
+        }),
+        LinkedNode::Default(LinkedDefault { ty, value }) => LinkedNode::Default(LinkedDefault {
+            ty: Box::new(map_aliases(*ty, f)),
+            value,
+        }),
+        LinkedNode::Dictionary(LinkedDictionary { min, max, key, val }) => {
+            LinkedNode::Dictionary(LinkedDictionary {
+                min,
+                max,
+                key: Box::new(map_aliases(*key, f)),
+                val: Box::new(map_aliases(*val, f)),
+            })
+        }
+        LinkedNode::Struct(f) => LinkedNode::Struct(fields(f)),
+        LinkedNode::Record(f) => LinkedNode::Record(fields(f)),
+        LinkedNode::Fields(f) => LinkedNode::Fields(fields(f)),
+        LinkedNode::Choice(LinkedChoice { variants }) => LinkedNode::Choice(LinkedChoice {
+            variants: fields(Fields { members: variants }).members,
+        }),
+        LinkedNode::Union(LinkedUnion {
+            discriminant,
+            variants,
+        }) => LinkedNode::Union(LinkedUnion {
+            discriminant,
+            variants: fields(Fields { members: variants }).members,
+        }),
+        node => node,
+    }
+}
+
+/// An alias that is rendered with the name of the type it names (IE: u8 = uint .size 1 in C)
+/// would name itself, so it is not rendered and the type is used instead
+fn self_named(name: &str, node: &LinkedNode, map: &HashMap<String, Value>) -> bool {
+    let aliased = matches!(
+        node,
+        LinkedNode::ConstrainedType(_)
+            | LinkedNode::Range(_)
+            | LinkedNode::Array(_)
+            | LinkedNode::Tagged(_)
+            | LinkedNode::Alias(_)
+    );
+    match (caseify(name, "struct", map), aliased) {
+        (Ok(name), true) => rust_type(node, map).is_ok_and(|ty| ty == name),
+        (_, _) => false,
+    }
+}
+
+/// An array or vector named by a rule is rendered as a type that holds it's items, except for an
+/// array of bytes which is a byte string
+fn array_kind(node: &LinkedNode) -> bool {
+    match node {
+        LinkedNode::Array(arr) => !is_bytes(arr),
+        LinkedNode::Vector(_) => true,
+        _ => false,
+    }
+}
+
+/// Unconstrained primatives are owned types (IE: String or Vec<u8>) which the C ABI cannot store
+fn unconstrained(node: &LinkedNode) -> bool {
+    match node {
+        LinkedNode::ConstrainedType(ty) => matches!(
+            ty,
+            ConstrainedType::Int
+                | ConstrainedType::UInt
+                | ConstrainedType::TStr
+                | ConstrainedType::BStr
+                | ConstrainedType::Any
+        ),
+        LinkedNode::Optional(node)
+        | LinkedNode::Tagged(LinkedTagged { ty: node, .. })
+        | LinkedNode::Alias(LinkedAlias { ty: node, .. })
+        | LinkedNode::Default(LinkedDefault { ty: node, .. }) => unconstrained(node),
+        LinkedNode::Regexp(LinkedRegexp { ty, .. }) => *ty == ConstrainedType::TStr,
+        LinkedNode::Embedded(LinkedEmbedded { max, .. }) => *max == usize::MAX,
+        LinkedNode::Array(LinkedArray { ty, .. }) | LinkedNode::Vector(LinkedVector { ty, .. }) => {
+            unconstrained(ty)
+        }
+        // An unbounded dictionary is stored in a BTreeMap
+        LinkedNode::Dictionary(LinkedDictionary { max, key, val, .. }) => {
+            *max == usize::MAX || unconstrained(key) || unconstrained(val)
+        }
+        LinkedNode::Struct(fields) | LinkedNode::Record(fields) | LinkedNode::Fields(fields) => {
+            fields
+                .members
+                .iter()
+                .any(|LinkedKeyVal(_, node, ..)| unconstrained(node))
+        }
+        LinkedNode::Choice(LinkedChoice { variants, .. })
+        | LinkedNode::Union(LinkedUnion { variants, .. }) => variants
+            .iter()
+            .any(|LinkedKeyVal(_, node, ..)| unconstrained(node)),
+        _ => false,
+    }
+}
+
+/// A recursive type is boxed, which the C ABI cannot store
+fn recursive(node: &LinkedNode) -> bool {
+    match node {
+        LinkedNode::Boxed(_) => true,
+        LinkedNode::Optional(node)
+        | LinkedNode::Vector(LinkedVector { ty: node, .. })
+        | LinkedNode::Dictionary(LinkedDictionary { val: node, .. }) => recursive(node),
+        LinkedNode::Struct(fields) | LinkedNode::Record(fields) | LinkedNode::Fields(fields) => {
+            fields
+                .members
+                .iter()
+                .any(|LinkedKeyVal(_, node, ..)| recursive(node))
+        }
+        LinkedNode::Union(LinkedUnion { variants, .. }) => variants
+            .iter()
+            .any(|LinkedKeyVal(_, node, ..)| recursive(node)),
+        _ => false,
+    }
+}
+
 #[derive(PartialEq, Debug, Clone, Serialize, Deserialize)]
 #[serde(rename_all = "lowercase")]
 enum Case {
@@ -122,46 +462,162 @@ fn filter_rename(val: &Value, map: &HashMap<String, Value>) -> Result<Value> {
         .map(Value::String)
 }
 
-/// Take a Literal and declare a const type
+/// Take a Literal and describe the constant it is declared as. A constant keeps the type of it's
+/// literal, text is escaped and bytes are written as a byte string. In C a constant of bytes is a
+/// static, as cbindgen only writes a #define for a constant of a primitive or text. Javascript
+/// cannot import a constant, so it is returned by a function instead
 fn filter_literal(val: &Value, map: &HashMap<String, Value>) -> Result<Value> {
-    // get the name of the literal and format to "SHOUTY_SNAKE_CASE"
-    let name = map
-        .get("name")
-        .and_then(|val| val.as_str())
-        .ok_or_else(|| TeraError::msg(format!("unexpected value for name filter")))
-        .and_then(|name| caseify(name, "const", map))?;
-    // get the enum of our type
     let lit = from_value::<Literal>(val.clone())?;
-    match lit {
-        Literal::Bool(b) => Ok(format!("pub const {}: bool = {};", name, b)),
-        Literal::Int(i) => Ok(format!("pub const {}: i32 = {};", name, i)),
-        Literal::UInt(u) => Ok(format!("pub const {}: u32 = {};", name, u)),
-        Literal::Str(s) => Ok(format!("pub const {}: 'static str = \"{}\";", name, s)),
-        Literal::Char(c) => Ok(format!("pub const {}: char = '{}';", name, c)),
-        Literal::Bytes(_b) => Err(TeraError::msg(format!("unsupported literal"))),
-    }
-    .map(Value::String)
+    let lang = language(map);
+    let (ty, value, js_ty, js_value) = match (&lit, &lang) {
+        (Literal::Bool(b), _) => ("bool".into(), b.to_string(), "bool", ""),
+        (Literal::Int(i), _) => ("i64".into(), i.to_string(), "i64", ""),
+        (Literal::UInt(u), _) => ("u64".into(), u.to_string(), "u64", ""),
+        (Literal::Str(s), Language::C) => ("&str".into(), c_string(s), "String", ".to_string()"),
+        (Literal::Str(s), _) => ("&str".into(), format!("{:?}", s), "String", ".to_string()"),
+        (Literal::Bytes(b), Language::C) => (
+            format!("[u8; {}]", b.len()),
+            format!("*{}", byte_string(b)),
+            "Vec<u8>",
+            ".to_vec()",
+        ),
+        (Literal::Bytes(b), _) => ("&[u8]".into(), byte_string(b), "Vec<u8>", ".to_vec()"),
+        (Literal::Null, _) => return Err(TeraError::msg("a null literal has no value")),
+    };
+    Ok(json!({
+        "ty": ty,
+        "value": value,
+        "js_ty": js_ty,
+        "js_value": js_value,
+        "static": matches!(lit, Literal::Bytes(_)) && lang == Language::C,
+    }))
+}
+
+/// Render text as a string literal that is valid rust and valid C, as cbindgen copies the literal
+/// of a constant into it's #define. Only escapes both languages share are written, and a hex digit
+/// that follows a hex escape is escaped too, as C would read it as part of the escape. Other
+/// characters are written as UTF-8
+fn c_string(text: &str) -> String {
+    let mut hex = false;
+    let escaped = text
+        .chars()
+        .map(|c| {
+            let escaped = match c {
+                '"' => "\\\"".to_string(),
+                '\\' => "\\\\".to_string(),
+                '\n' => "\\n".to_string(),
+                '\r' => "\\r".to_string(),
+                '\t' => "\\t".to_string(),
+                c if c.is_ascii_control() || (hex && c.is_ascii_hexdigit()) => {
+                    format!("\\x{:02x}", c as u32)
+                }
+                c => c.to_string(),
+            };
+            hex = escaped.starts_with("\\x");
+            escaped
+        })
+        .collect::<String>();
+    format!("\"{}\"", escaped)
+}
+
+/// Render bytes as a byte string literal, where bytes that are not printable are escaped
+fn byte_string(bytes: &[u8]) -> String {
+    let escaped = bytes
+        .iter()
+        .flat_map(|b| std::ascii::escape_default(*b))
+        .map(char::from)
+        .collect::<String>();
+    format!("b\"{}\"", escaped)
 }
 
 /// Our HashMap of CDDL linked nodes can be filtered based on kind of node it is (aka struct or
-/// literal, etc)
+/// literal, etc). The value may be a single kind or a list of kinds
 fn filter_nodes(val: &Value, map: &HashMap<String, Value>) -> Result<Value> {
-    let value = map
+    let values = map
         .get("value")
-        .and_then(|val| val.as_str())
+        .and_then(|val| match val {
+            Value::String(s) => Some(vec![s.clone()]),
+            val => from_value::<Vec<String>>(val.clone()).ok(),
+        })
         .ok_or_else(|| TeraError::msg(format!("unexpected value for name filter")))?;
     let cddl = from_value::<BTreeMap<String, LinkedNode>>(val.clone())?;
     let filtered = cddl
         .into_iter()
-        .filter(|(_key, node)| match node {
-            LinkedNode::Struct(_) if value == "struct" => true,
-            LinkedNode::Literal(_) if value == "literal" => true,
-            _ => false,
+        .filter(|(_key, node)| {
+            let kind = match node {
+                LinkedNode::Struct(_) => "struct",
+                LinkedNode::Record(_) => "record",
+                LinkedNode::Fields(_) => "group",
+                LinkedNode::Dictionary(_) => "dictionary",
+                LinkedNode::Bits(_) => "bits",
+                LinkedNode::Regexp(_) => "regexp",
+                LinkedNode::Embedded(_) => "embedded",
+                node if array_kind(node) => "array",
+                LinkedNode::Literal(_) => "literal",
+                LinkedNode::Choice(c) if is_enum(c) => "enum",
+                LinkedNode::Choice(_) => "choice",
+                LinkedNode::Union(_) => "union",
+                LinkedNode::ConstrainedType(_)
+                | LinkedNode::Range(_)
+                | LinkedNode::Array(_)
+                | LinkedNode::Tagged(_)
+                | LinkedNode::Alias(_)
+                | LinkedNode::ForeignStruct(_)
+                | LinkedNode::ForeignChoice(_) => "alias",
+                _ => return false,
+            };
+            values.iter().any(|value| value == kind)
         })
         .collect::<BTreeMap<String, LinkedNode>>();
     to_value(filtered).map_err(|_| TeraError::msg(format!("infallible conversion failure")))
 }
 
+/// A choice of literal values is rendered as a C style enum instead of a tagged union
+fn is_enum(choice: &LinkedChoice) -> bool {
+    choice
+        .variants
+        .iter()
+        .all(|LinkedKeyVal(_, node, ..)| matches!(node, LinkedNode::Literal(_)))
+}
+
+/// Find the smallest integer type that can represent every discriminant of an enum, or "str"
+/// if the enum is a choice of text values
+fn filter_enum_repr(val: &Value, _map: &HashMap<String, Value>) -> Result<Value> {
+    let choice = from_value::<LinkedChoice>(val.clone())?;
+    let values = choice
+        .variants
+        .iter()
+        .map(|LinkedKeyVal(_, node, ..)| match node {
+            LinkedNode::Literal(Literal::UInt(u)) => Ok(*u as i128),
+            LinkedNode::Literal(Literal::Int(i)) => Ok(*i as i128),
+            _ => Err(()),
+        })
+        .collect::<std::result::Result<Vec<i128>, ()>>();
+    match values {
+        Err(_) => Ok(Value::String("str".into())),
+        Ok(values) => {
+            let min = values.iter().copied().min().unwrap_or_default();
+            let max = values.iter().copied().max().unwrap_or_default();
+            Ok(to_value(ConstrainedType::fit(min, max))?)
+        }
+    }
+}
+
+/// Take the Literal of a LinkedKeyVal and render it as a rust expression
+fn filter_literal_value(val: &Value, _map: &HashMap<String, Value>) -> Result<Value> {
+    match from_value::<LinkedKeyVal>(val.clone())? {
+        LinkedKeyVal(_, LinkedNode::Literal(lit), _) => match lit {
+            Literal::Bool(b) => Ok(b.to_string()),
+            Literal::Int(i) => Ok(i.to_string()),
+            Literal::UInt(u) => Ok(u.to_string()),
+            Literal::Str(s) => Ok(format!("{:?}", s)),
+            Literal::Bytes(_) | Literal::Null => Err(TeraError::msg("unsupported literal")),
+        },
+        _ => Err(TeraError::msg("expected literal")),
+    }
+    .map(Value::String)
+}
+
 fn to_fn_case(name: &str, verb: &str, lang: Language, prefix: Option<String>) -> String {
     match (lang, prefix) {
         (_, Some(prefix)) => format!("{}{}_{}", prefix, verb, name).to_snake_case(),
@@ -222,6 +678,22 @@ fn caseify(s: &str, case: &str, opts: &HashMap<String, Value>) -> Result<String>
         Ok(to_fn_case(s, verb, lang, pre))
     } else if case == "enum" || case == "const" || case == "define" {
         Ok(to_shouty_snake_case(s, lang, pre))
+    } else if case == "variant" {
+        // Rust enum variants are CamelCase, C enum variants are namespaced by their parent
+        let parent = opts
+            .get("parent")
+            .and_then(|val| val.as_str())
+            .ok_or_else(|| TeraError::msg("variant rename requires a parent"))?;
+        match lang {
+            Language::C => Ok(to_shouty_snake_case(
+                &format!("{}-{}", parent, s),
+                lang,
+                pre,
+            )),
+            Language::Rust | Language::Typescript => Ok(to_upper_camel_case(s, lang, pre)),
+        }
+    } else if case == "field" {
+        Ok(s.to_snake_case())
     } else if case == "lowerCamelCase" {
         Ok(to_lower_camel_case(s, lang, pre))
     } else {
@@ -229,78 +701,1190 @@ fn caseify(s: &str, case: &str, opts: &HashMap<String, Value>) -> Result<String>
     }
 }
 
-fn filter_field_default(val: &Value, _map: &HashMap<String, Value>) -> Result<Value> {
-    let LinkedKeyVal(key, val) = from_value::<LinkedKeyVal>(val.clone())
-        .map(|LinkedKeyVal(key, val)| LinkedKeyVal(key.to_snake_case(), val))?;
-    match val {
-        LinkedNode::Array(LinkedArray { ty, len }) => match *ty {
-            LinkedNode::ConstrainedType(ConstrainedType::U8) => {
-                Ok(format!("{}: [0; {}]", key, len))
-            }
-            _ => unimplemented!(),
+fn filter_field_default(val: &Value, map: &HashMap<String, Value>) -> Result<Value> {
+    let LinkedKeyVal(key, val, ..) = from_value::<LinkedKeyVal>(val.clone())
+        .map(|LinkedKeyVal(key, val, wire)| LinkedKeyVal(key.to_snake_case(), val, wire))?;
+    match (val, language(map)) {
+        (LinkedNode::Optional(val), Language::C) => {
+            let member = to_value(LinkedKeyVal::new(key.clone(), *val))?;
+            filter_field_default(&member, map).map(|default| {
+                Value::String(format!(
+                    "has_{}: false, {}",
+                    key,
+                    default.as_str().unwrap_or("")
+                ))
+            })
+        }
+        (LinkedNode::Vector(LinkedVector { ty, min, .. }), Language::C) => {
+            Ok(Value::String(format!(
+                "{key}: core::array::from_fn(|_| {default}), {key}_len: {min}",
+                key = key,
+                default = rust_default(&ty),
+                min = min
+            )))
+        }
+        (val, _) => Ok(Value::String(format!("{}: {}", key, rust_default(&val)))),
+    }
+}
+
+/// Similar to field_default, but without the key (useful for tuple variants)
+fn filter_default_value(val: &Value, _map: &HashMap<String, Value>) -> Result<Value> {
+    let LinkedKeyVal(_key, val, ..) = from_value::<LinkedKeyVal>(val.clone())?;
+    Ok(Value::String(rust_default(&val)))
+}
+
+/// Large arrays do not implement Default, so we spell them out. Ranges default to zero when they
+/// allow it, else to their lowest allowed value. Vectors default to their least number of entries,
+/// and a type with a .default control defaults to it's value
+fn rust_default(node: &LinkedNode) -> String {
+    if let Some((ty, value)) = default_of(node) {
+        return default_expr(ty, value);
+    }
+    match unaliased(node) {
+        LinkedNode::Range(LinkedRange { min, max, ne, .. }) => {
+            let ne = ne.as_ref().and_then(Literal::as_int);
+            min.as_int()
+                .into_iter()
+                .flat_map(|min| [0, min, min + 1])
+                .find(|val| range_contains(min, max, *val) && Some(*val) != ne)
+                .map(|val| val.to_string())
+                .unwrap_or_else(|| literal_int(min))
+        }
+        LinkedNode::ConstrainedType(ConstrainedType::Str(len)) => format!("[0; {}]", len),
+        LinkedNode::Array(LinkedArray { ty, len }) => match unaliased(ty) {
+            LinkedNode::ConstrainedType(ConstrainedType::U8) => format!("[0; {}]", len),
+            _ => format!("core::array::from_fn(|_| {})", rust_default(ty)),
+        },
+        LinkedNode::Vector(LinkedVector { min: 0, .. }) => "heapless::Vec::new()".to_string(),
+        LinkedNode::Vector(LinkedVector { ty, min, .. }) => {
+            format!("(0..{}).map(|_| {}).collect()", min, rust_default(ty))
+        }
+        LinkedNode::Tagged(LinkedTagged { ty, .. }) => rust_default(ty),
+        _ => "Default::default()".to_string(),
+    }
+}
+
+/// The type and the value of a type with a .default control, which may be named by an alias
+fn default_of(node: &LinkedNode) -> Option<(&LinkedNode, &Literal)> {
+    match node {
+        LinkedNode::Alias(LinkedAlias { ty, .. }) => default_of(ty),
+        LinkedNode::Default(LinkedDefault { ty, value }) => Some((ty, value)),
+        _ => None,
+    }
+}
+
+/// Render the value of a .default control as a value of it's type. Text of a fixed size is padded
+/// with zeros
+fn default_expr(ty: &LinkedNode, value: &Literal) -> String {
+    match (unaliased(ty), value) {
+        (LinkedNode::ConstrainedType(ConstrainedType::Str(len)), Literal::Str(s)) => {
+            let mut bytes = s.as_bytes().to_vec();
+            bytes.resize(*len as usize, 0);
+            format!("{:?}", bytes)
+        }
+        (LinkedNode::ConstrainedType(ConstrainedType::TStr), Literal::Str(s)) => {
+            format!("String::from({:?})", s)
+        }
+        (_, Literal::Bool(b)) => b.to_string(),
+        (_, lit) => literal_int(lit),
+    }
+}
+
+/// Render a typed rust expression of a literal
+fn literal_expr(lit: &Literal) -> Result<(&'static str, String)> {
+    match lit {
+        Literal::Bool(b) => Ok(("bool", b.to_string())),
+        Literal::Int(i) => Ok(("i64", format!("{}i64", i))),
+        Literal::UInt(u) => Ok(("u64", format!("{}u64", u))),
+        Literal::Str(s) => Ok(("&str", format!("{:?}", s))),
+        Literal::Bytes(b) => Ok((
+            "&minicbor::bytes::ByteSlice",
+            format!(
+                "<&minicbor::bytes::ByteSlice>::from(&{}[..])",
+                byte_string(b)
+            ),
+        )),
+        Literal::Null => Err(TeraError::msg("unsupported literal")),
+    }
+}
+
+/// The expression of a member, which is a field of self unless the caller provides one
+fn member_expr(key: &str, map: &HashMap<String, Value>) -> String {
+    map.get("expr")
+        .and_then(|val| val.as_str())
+        .map(|expr| expr.to_string())
+        .unwrap_or_else(|| format!("self.{}", key.to_snake_case()))
+}
+
+/// The language of the options passed to a filter
+fn language(map: &HashMap<String, Value>) -> Language {
+    map.get("options")
+        .and_then(|val| from_value::<Options>(val.clone()).ok())
+        .map(|opts| opts.language)
+        .unwrap_or_default()
+}
+
+/// The position of a member in the array of a struct with a manual codec
+fn member_index(map: &HashMap<String, Value>) -> Result<u64> {
+    map.get("index")
+        .and_then(|i| i.as_u64())
+        .ok_or_else(|| TeraError::msg("expected number"))
+}
+
+/// Render the statement that encodes a member of a struct with a manual Encode impl. Literal
+/// members are not stored and are always written. Optional members are written as null when
+/// absent, unless they are at the end of the array
+fn filter_cbor_encode(val: &Value, map: &HashMap<String, Value>) -> Result<Value> {
+    let LinkedKeyVal(key, node, ..) = from_value::<LinkedKeyVal>(val.clone())?;
+    let expr = member_expr(&key, map);
+    match (&node, language(map)) {
+        (LinkedNode::Literal(Literal::Null), _) => Ok("e.null()?;".into()),
+        (LinkedNode::Literal(lit), _) => {
+            literal_expr(lit).map(|(_, lit)| format!("Encode::encode(&{}, e, ctx)?;", lit))
+        }
+        (LinkedNode::Optional(node), Language::C) => Ok(format!(
+            "if len > {} {{ if self.has_{} {{ {} }} else {{ e.null()?; }} }}",
+            member_index(map)?,
+            key.to_snake_case(),
+            encode_stmt_c(node, &expr)
+        )),
+        (LinkedNode::Optional(node), _) => Ok(format!(
+            "if len > {} {{ match &{} {{ Some(val) => {{ {} }} None => {{ e.null()?; }} }} }}",
+            member_index(map)?,
+            expr,
+            encode_stmt(node, "*val")
+        )),
+        (node, Language::C) => Ok(encode_stmt_c(node, &expr)),
+        (node, _) => Ok(encode_stmt(node, &expr)),
+    }
+    .map(Value::String)
+}
+
+/// A C struct stores a vector in an array paired with a length
+fn encode_stmt_c(node: &LinkedNode, expr: &str) -> String {
+    match node {
+        LinkedNode::Vector(vec) => format!(
+            "__bindgen::encode_items({}, e, ctx)?;",
+            vector_items(expr, vec)
+        ),
+        node => encode_stmt(node, expr),
+    }
+}
+
+fn encode_stmt(node: &LinkedNode, expr: &str) -> String {
+    match (unaliased(node), codec_with(node)) {
+        (LinkedNode::Vector(_), _) => format!("__bindgen::encode_vec(&{}, e, ctx)?;", expr),
+        (LinkedNode::Array(arr), _) if !is_bytes(arr) => {
+            format!("{}(&{}, e, ctx)?;", array_fn(arr, "encode"), expr)
+        }
+        (LinkedNode::Tagged(tagged), _) => format!(
+            "{}(&{}, e, ctx)?;",
+            codec_fn(&tagged_codec(tagged), "encode"),
+            expr
+        ),
+        (_, Some(with)) => format!("{}::encode(&{}, e, ctx)?;", with, expr),
+        (_, None) => format!("Encode::encode(&{}, e, ctx)?;", expr),
+    }
+}
+
+/// The entries of a vector in C are the entries of it's array up to it's length
+fn vector_items(expr: &str, vec: &LinkedVector) -> String {
+    format!(
+        "&{expr}[..core::cmp::min({expr}_len as usize, {max})]",
+        expr = expr,
+        max = vec.max
+    )
+}
+
+/// Render the statement that decodes a member of a struct with a manual Decode impl into a
+/// local binding. Literal members are not stored and are checked instead
+fn filter_cbor_decode(val: &Value, map: &HashMap<String, Value>) -> Result<Value> {
+    let LinkedKeyVal(key, node, ..) = from_value::<LinkedKeyVal>(val.clone())?;
+    let field = key.to_snake_case();
+    let present = |n| format!("len > {} && d.datatype()? != minicbor::data::Type::Null", n);
+    match (&node, language(map)) {
+        (LinkedNode::Literal(Literal::Null), _) => Ok("d.null()?;".into()),
+        (LinkedNode::Literal(lit), _) => literal_expr(lit).map(|(ty, lit)| {
+            format!(
+                r#"{{
+                    let p = d.position();
+                    let val: {} = Decode::decode(d, ctx)?;
+                    if val != {} {{
+                        return Err(minicbor::decode::Error::message("unexpected {} value").at(p));
+                    }}
+                }}"#,
+                ty, lit, key
+            )
+        }),
+        (LinkedNode::Optional(node), Language::C) => {
+            let (binding, decode, default) = decode_c(&field, node);
+            Ok(format!(
+                r#"let (f_has_{field}, {binding}) = if {present} {{
+                    (true, {decode})
+                }} else {{
+                    if len > {n} {{
+                        d.skip()?;
+                    }}
+                    (false, {default})
+                }};"#,
+                field = field,
+                binding = binding,
+                present = present(member_index(map)?),
+                decode = decode,
+                n = member_index(map)?,
+                default = default
+            ))
+        }
+        (LinkedNode::Optional(node), _) => Ok(format!(
+            r#"let f_{field} = if {present} {{
+                Some({decode})
+            }} else {{
+                if len > {n} {{
+                    d.skip()?;
+                }}
+                None
+            }};"#,
+            field = field,
+            present = present(member_index(map)?),
+            decode = decode_expr(node),
+            n = member_index(map)?
+        )),
+        (node @ LinkedNode::Vector(_), Language::C) => {
+            let (binding, decode, _) = decode_c(&field, node);
+            Ok(format!("let {} = {};", binding, decode))
+        }
+        (node, _) => Ok(format!("let f_{} = {};", field, decode_expr(node))),
+    }
+    .map(Value::String)
+}
+
+/// The local binding of a member decoded in C, the expression that decodes it and the value of
+/// the binding when the member is absent
+fn decode_c(field: &str, node: &LinkedNode) -> (String, String, String) {
+    match node {
+        LinkedNode::Vector(vec) => (
+            format!("(f_{field}, f_{field}_len)", field = field),
+            format!(
+                "{}(d, ctx)?",
+                vector_decoder("__bindgen::decode_items", vec)
+            ),
+            format!("(core::array::from_fn(|_| {}), 0)", rust_default(&vec.ty)),
+        ),
+        node => (
+            format!("f_{}", field),
+            decode_expr(node),
+            rust_default(node),
+        ),
+    }
+}
+
+/// Render the initializer of a member decoded by cbor_decode
+fn filter_cbor_init(val: &Value, map: &HashMap<String, Value>) -> Result<Value> {
+    let LinkedKeyVal(key, node, ..) = from_value::<LinkedKeyVal>(val.clone())?;
+    let field = key.to_snake_case();
+    match (&node, language(map)) {
+        (LinkedNode::Literal(_), _) => Ok("".into()),
+        (LinkedNode::Optional(node), Language::C) => {
+            let member = to_value(LinkedKeyVal::new(key.clone(), (**node).clone()))?;
+            filter_cbor_init(&member, map).map(|init| {
+                format!(
+                    "has_{field}: f_has_{field}, {init}",
+                    field = field,
+                    init = init.as_str().unwrap_or("")
+                )
+            })
+        }
+        (LinkedNode::Vector(_), Language::C) => Ok(format!(
+            "{field}: f_{field}, {field}_len: f_{field}_len,",
+            field = field
+        )),
+        (_, _) => Ok(format!("{field}: f_{field},", field = field)),
+    }
+    .map(Value::String)
+}
+
+/// Similar to cbor_decode, but only the expression (useful for tuple variants)
+fn filter_decode_expr(val: &Value, _map: &HashMap<String, Value>) -> Result<Value> {
+    let LinkedKeyVal(_key, node, ..) = from_value::<LinkedKeyVal>(val.clone())?;
+    Ok(Value::String(decode_expr(&node)))
+}
+
+fn decode_expr(node: &LinkedNode) -> String {
+    match (unaliased(node), codec_with(node)) {
+        (LinkedNode::Range(range), _) => format!("{}(d, ctx)?", range_decoder(range)),
+        (LinkedNode::Vector(vec), _) => {
+            format!("{}(d, ctx)?", vector_decoder("__bindgen::decode_vec", vec))
+        }
+        (LinkedNode::Array(arr), _) if !is_bytes(arr) => {
+            format!("{}(d, ctx)?", array_fn(arr, "decode"))
+        }
+        (LinkedNode::Tagged(tagged), _) => {
+            format!("{}(d, ctx)?", codec_fn(&tagged_codec(tagged), "decode"))
+        }
+        (_, Some(with)) => format!("{}::decode(d, ctx)?", with),
+        (_, None) => "Decode::decode(d, ctx)?".into(),
+    }
+}
+
+/// Render the expression of the encoded length of a member of a struct with a manual CborLen
+/// impl
+fn filter_cbor_len(val: &Value, map: &HashMap<String, Value>) -> Result<Value> {
+    let LinkedKeyVal(key, node, ..) = from_value::<LinkedKeyVal>(val.clone())?;
+    let expr = member_expr(&key, map);
+    match (&node, language(map)) {
+        (LinkedNode::Literal(Literal::Null), _) => Ok("1".into()),
+        (LinkedNode::Literal(lit), _) => {
+            literal_expr(lit).map(|(_, lit)| format!("CborLen::cbor_len(&{}, ctx)", lit))
+        }
+        (LinkedNode::Optional(node), Language::C) => Ok(format!(
+            "(match (len > {}, self.has_{}) {{ (true, true) => {}, (true, false) => 1, _ => 0 }})",
+            member_index(map)?,
+            key.to_snake_case(),
+            len_expr_c(node, &expr)
+        )),
+        (LinkedNode::Optional(node), _) => Ok(format!(
+            "(match (len > {}, &{}) {{ (true, Some(val)) => {}, (true, None) => 1, _ => 0 }})",
+            member_index(map)?,
+            expr,
+            len_expr(node, "*val")
+        )),
+        (node, Language::C) => Ok(len_expr_c(node, &expr)),
+        (node, _) => Ok(len_expr(node, &expr)),
+    }
+    .map(Value::String)
+}
+
+fn len_expr_c(node: &LinkedNode, expr: &str) -> String {
+    match node {
+        LinkedNode::Vector(vec) => {
+            format!(
+                "__bindgen::cbor_len_items({}, ctx)",
+                vector_items(expr, vec)
+            )
+        }
+        node => len_expr(node, expr),
+    }
+}
+
+fn len_expr(node: &LinkedNode, expr: &str) -> String {
+    match (unaliased(node), codec_with(node)) {
+        (LinkedNode::Vector(_), _) => format!("__bindgen::cbor_len_vec(&{}, ctx)", expr),
+        (LinkedNode::Array(arr), _) if !is_bytes(arr) => {
+            format!("{}(&{}, ctx)", array_fn(arr, "cbor_len"), expr)
+        }
+        (LinkedNode::Tagged(tagged), _) => format!(
+            "{}(&{}, ctx)",
+            codec_fn(&tagged_codec(tagged), "cbor_len"),
+            expr
+        ),
+        (_, Some(with)) => format!("{}::cbor_len(&{}, ctx)", with, expr),
+        (_, None) => format!("CborLen::cbor_len(&{}, ctx)", expr),
+    }
+}
+
+/// Render the expression of the length of the array of a struct with a manual codec. Absent
+/// optional members at the end of the array are not encoded
+fn filter_cbor_array_len(val: &Value, map: &HashMap<String, Value>) -> Result<Value> {
+    let fields = from_value::<Fields>(val.clone())?;
+    let required = required_len(&fields);
+    let lang = language(map);
+    let expr = fields.members[required..].iter().enumerate().rev().fold(
+        required.to_string(),
+        |acc, (n, LinkedKeyVal(key, ..))| {
+            let present = match lang {
+                Language::C => format!("self.has_{}", key.to_snake_case()),
+                _ => format!("self.{}.is_some()", key.to_snake_case()),
+            };
+            format!(
+                "if {} {{ {} }} else {{ {} }}",
+                present,
+                required + n + 1,
+                acc
+            )
         },
-        _ => Ok(format!("{}: Default::default()", key)),
+    );
+    Ok(Value::String(expr))
+}
+
+/// Render the least number of members in the array of a struct with a manual codec
+fn filter_cbor_array_min(val: &Value, _map: &HashMap<String, Value>) -> Result<Value> {
+    let fields = from_value::<Fields>(val.clone())?;
+    Ok(Value::String(required_len(&fields).to_string()))
+}
+
+fn required_len(fields: &Fields) -> usize {
+    fields
+        .members
+        .iter()
+        .rposition(|LinkedKeyVal(_, node, ..)| !matches!(node, LinkedNode::Optional(_)))
+        .map(|n| n + 1)
+        .unwrap_or_default()
+}
+
+/// Render the number of entries in the map of a struct, which are the required members and the
+/// optional members that are present
+fn filter_cbor_map_len(val: &Value, map: &HashMap<String, Value>) -> Result<Value> {
+    let fields = from_value::<Fields>(val.clone())?;
+    let lang = language(map);
+    let (optional, required): (Vec<&LinkedKeyVal>, Vec<&LinkedKeyVal>) = fields
+        .members
+        .iter()
+        .partition(|LinkedKeyVal(_, node, ..)| matches!(node, LinkedNode::Optional(_)));
+    let expr =
+        optional.iter().fold(
+            required.len().to_string(),
+            |acc, LinkedKeyVal(key, ..)| match lang {
+                Language::C => format!("{} + self.has_{} as usize", acc, key.to_snake_case()),
+                _ => format!("{} + self.{}.is_some() as usize", acc, key.to_snake_case()),
+            },
+        );
+    Ok(Value::String(expr))
+}
+
+/// Render the statement that encodes the key of the entry of a member of a map. A member is keyed
+/// by it's name unless it declares an integer or a quoted text key
+fn key_stmt(key: &str, wire: &Option<Literal>) -> String {
+    match wire {
+        Some(Literal::UInt(n)) => format!("e.u64({})?;", n),
+        Some(Literal::Int(n)) => format!("e.i64({})?;", n),
+        Some(Literal::Str(s)) => format!("e.str({:?})?;", s),
+        _ => format!("e.str({:?})?;", key),
+    }
+}
+
+/// Render the pattern that matches the key of the entry of a member of a map. Text keys are
+/// matched as a str, integer keys as an i128
+fn key_pattern(key: &str, wire: &Option<Literal>) -> String {
+    match wire {
+        Some(Literal::UInt(n)) => n.to_string(),
+        Some(Literal::Int(n)) => n.to_string(),
+        Some(Literal::Str(s)) => format!("{:?}", s),
+        _ => format!("{:?}", key),
+    }
+}
+
+/// Render the expression of the encoded length of the key of the entry of a member of a map
+fn key_len(key: &str, wire: &Option<Literal>) -> String {
+    match wire {
+        Some(Literal::UInt(n)) => format!("CborLen::cbor_len(&{}u64, ctx)", n),
+        Some(Literal::Int(n)) => format!("CborLen::cbor_len(&({}i64), ctx)", n),
+        Some(Literal::Str(s)) => format!("CborLen::cbor_len({:?}, ctx)", s),
+        _ => format!("CborLen::cbor_len({:?}, ctx)", key),
+    }
+}
+
+fn is_int_key(wire: &Option<Literal>) -> bool {
+    matches!(wire, Some(Literal::UInt(_) | Literal::Int(_)))
+}
+
+/// The data types of an integer key of a map
+const INT_KEY_TYPES: &str = "minicbor::data::Type::U8
+    | minicbor::data::Type::U16
+    | minicbor::data::Type::U32
+    | minicbor::data::Type::U64
+    | minicbor::data::Type::I8
+    | minicbor::data::Type::I16
+    | minicbor::data::Type::I32
+    | minicbor::data::Type::I64
+    | minicbor::data::Type::Int";
+
+/// Select the members of a map by the kind of their key, "text" or "int", so that the decoder can
+/// match each kind of key separately
+fn filter_cbor_map_keys(val: &Value, map: &HashMap<String, Value>) -> Result<Value> {
+    let fields = from_value::<Fields>(val.clone())?;
+    let int = match map.get("kind").and_then(|kind| kind.as_str()) {
+        Some("text") => false,
+        Some("int") => true,
+        _ => {
+            return Err(TeraError::msg(
+                "cbor_map_keys requires a kind of text or int",
+            ))
+        }
+    };
+    let members = fields
+        .members
+        .into_iter()
+        .filter(|LinkedKeyVal(_, _, wire)| is_int_key(wire) == int)
+        .collect::<Vec<LinkedKeyVal>>();
+    Ok(to_value(members)?)
+}
+
+/// Render the statement that encodes the entry of a member of a struct with a map codec. Optional
+/// members are omitted when absent
+fn filter_cbor_map_encode(val: &Value, map: &HashMap<String, Value>) -> Result<Value> {
+    let LinkedKeyVal(key, node, wire) = from_value::<LinkedKeyVal>(val.clone())?;
+    let expr = member_expr(&key, map);
+    let entry = |stmt: String| format!("{} {}", key_stmt(&key, &wire), stmt);
+    match (&node, language(map)) {
+        (LinkedNode::Literal(Literal::Null), _) => Ok(entry("e.null()?;".into())),
+        (LinkedNode::Literal(lit), _) => {
+            literal_expr(lit).map(|(_, lit)| entry(format!("Encode::encode(&{}, e, ctx)?;", lit)))
+        }
+        (LinkedNode::Optional(node), Language::C) => Ok(format!(
+            "if self.has_{} {{ {} }}",
+            key.to_snake_case(),
+            entry(encode_stmt_c(node, &expr))
+        )),
+        (LinkedNode::Optional(node), _) => Ok(format!(
+            "if let Some(val) = &{} {{ {} }}",
+            expr,
+            entry(encode_stmt(node, "*val"))
+        )),
+        (node, Language::C) => Ok(entry(encode_stmt_c(node, &expr))),
+        (node, _) => Ok(entry(encode_stmt(node, &expr))),
+    }
+    .map(Value::String)
+}
+
+/// Render the local binding that holds a member of a struct with a map codec until the whole map
+/// is decoded
+fn filter_cbor_map_slot(val: &Value, map: &HashMap<String, Value>) -> Result<Value> {
+    let LinkedKeyVal(key, node, ..) = from_value::<LinkedKeyVal>(val.clone())?;
+    let ty = match &node {
+        LinkedNode::Literal(_) => "()".to_string(),
+        LinkedNode::Optional(node) => slot_type(node, map)?,
+        node => slot_type(node, map)?,
+    };
+    Ok(Value::String(format!(
+        "let mut f_{}: Option<{}> = None;",
+        key.to_snake_case(),
+        ty
+    )))
+}
+
+/// A vector in C is held with it's length
+fn slot_type(node: &LinkedNode, map: &HashMap<String, Value>) -> Result<String> {
+    match (node, language(map)) {
+        (LinkedNode::Vector(_), Language::C) => Ok(format!("({}, u32)", rust_type(node, map)?)),
+        (node, _) => rust_type(node, map),
+    }
+}
+
+/// Render the match arm that decodes the value of an entry of a map into it's local binding
+fn filter_cbor_map_decode(val: &Value, map: &HashMap<String, Value>) -> Result<Value> {
+    let LinkedKeyVal(key, node, wire) = from_value::<LinkedKeyVal>(val.clone())?;
+    let field = key.to_snake_case();
+    let decode = match (&node, language(map)) {
+        (LinkedNode::Literal(Literal::Null), _) => "{ d.null()?; }".to_string(),
+        (LinkedNode::Literal(lit), _) => literal_expr(lit).map(|(ty, lit)| {
+            format!(
+                r#"{{
+                    let p = d.position();
+                    let val: {} = Decode::decode(d, ctx)?;
+                    if val != {} {{
+                        return Err(minicbor::decode::Error::message("unexpected {} value").at(p));
+                    }}
+                }}"#,
+                ty, lit, key
+            )
+        })?,
+        (LinkedNode::Optional(node), Language::C) => decode_c(&field, node).1,
+        (LinkedNode::Optional(node), _) => decode_expr(node),
+        (node, Language::C) => decode_c(&field, node).1,
+        (node, _) => decode_expr(node),
+    };
+    Ok(Value::String(format!(
+        "{} => f_{} = Some({}),",
+        key_pattern(&key, &wire),
+        field,
+        decode
+    )))
+}
+
+/// Render the statement that takes a member out of it's local binding once the map is decoded.
+/// A required member that is missing is an error
+fn filter_cbor_map_take(val: &Value, map: &HashMap<String, Value>) -> Result<Value> {
+    let LinkedKeyVal(key, node, ..) = from_value::<LinkedKeyVal>(val.clone())?;
+    let field = key.to_snake_case();
+    let missing = format!(
+        r#".ok_or_else(|| minicbor::decode::Error::message("missing {} member").at(p))?"#,
+        key
+    );
+    match (&node, language(map)) {
+        (LinkedNode::Literal(_), _) => Ok(format!("f_{}{};", field, missing)),
+        (LinkedNode::Optional(node), Language::C) => {
+            let (binding, _, default) = decode_c(&field, node);
+            Ok(format!(
+                r#"let (f_has_{field}, {binding}) = match f_{field} {{
+                    Some(val) => (true, val),
+                    None => (false, {default}),
+                }};"#,
+                field = field,
+                binding = binding,
+                default = default
+            ))
+        }
+        (LinkedNode::Optional(_), _) => Ok("".into()),
+        (LinkedNode::Vector(_), Language::C) => Ok(format!(
+            "let (f_{field}, f_{field}_len) = f_{field}{missing};",
+            field = field,
+            missing = missing
+        )),
+        (_, _) => Ok(format!(
+            "let f_{field} = f_{field}{missing};",
+            field = field,
+            missing = missing
+        )),
     }
     .map(Value::String)
 }
 
+/// Render the expression of the encoded length of the entry of a member of a struct with a map
+/// codec
+fn filter_cbor_map_entry_len(val: &Value, map: &HashMap<String, Value>) -> Result<Value> {
+    let LinkedKeyVal(key, node, wire) = from_value::<LinkedKeyVal>(val.clone())?;
+    let expr = member_expr(&key, map);
+    let entry = |len: String| format!("{} + {}", key_len(&key, &wire), len);
+    match (&node, language(map)) {
+        (LinkedNode::Literal(Literal::Null), _) => Ok(entry("1".into())),
+        (LinkedNode::Literal(lit), _) => {
+            literal_expr(lit).map(|(_, lit)| entry(format!("CborLen::cbor_len(&{}, ctx)", lit)))
+        }
+        (LinkedNode::Optional(node), Language::C) => Ok(format!(
+            "(if self.has_{} {{ {} }} else {{ 0 }})",
+            key.to_snake_case(),
+            entry(len_expr_c(node, &expr))
+        )),
+        (LinkedNode::Optional(node), _) => Ok(format!(
+            "(match &{} {{ Some(val) => {}, None => 0 }})",
+            expr,
+            entry(len_expr(node, "*val"))
+        )),
+        (node, Language::C) => Ok(entry(len_expr_c(node, &expr))),
+        (node, _) => Ok(entry(len_expr(node, &expr))),
+    }
+    .map(Value::String)
+}
+
+/// Structs of a map are encoded by hand with the key of each member, unless every member is keyed
+/// by an integer the minicbor derive macros can represent (a u32). Structs of an array are
+/// encoded with the minicbor derive macros. In either case the struct is encoded by hand when it
+/// has members the derive macros cannot represent such as the presence flags of optional members
+/// in C, the length of vectors in C, or literal members which are not stored
+fn filter_struct_codec(val: &Value, map: &HashMap<String, Value>) -> Result<Value> {
+    let fields = from_value::<Fields>(val.clone())?;
+    let paired = fields.members.iter().any(|LinkedKeyVal(_, node, ..)| {
+        matches!(node, LinkedNode::Optional(_) | LinkedNode::Vector(_))
+    });
+    let paired = paired && language(map) == Language::C;
+    // A group is rendered like a struct, as a map of it's members
+    if matches!(
+        map.get("kind").and_then(|kind| kind.as_str()),
+        Some("struct") | Some("fields")
+    ) {
+        let derive = !fields.members.is_empty()
+            && fields.members.iter().all(|LinkedKeyVal(_, node, wire)| {
+                let indexed = matches!(wire, Some(Literal::UInt(n)) if *n <= u32::MAX as u64);
+                indexed && !matches!(node, LinkedNode::Literal(_))
+            });
+        return match derive && !paired {
+            true => Ok(Value::String("derive-map".into())),
+            false => Ok(Value::String("map".into())),
+        };
+    }
+    // A literal member is not stored, so it is written and checked by hand
+    let literal = fields
+        .members
+        .iter()
+        .any(|LinkedKeyVal(_, node, ..)| matches!(node, LinkedNode::Literal(_)));
+    match (paired, literal) {
+        (true, _) | (_, true) => Ok(Value::String("manual".into())),
+        (_, _) => Ok(Value::String("derive".into())),
+    }
+}
+
+/// A union is rendered as a choice of structs, one for each variant. Each variant carries the
+/// fields of it's struct and the value of it's discriminant, and the union carries the statement
+/// that peeks the discriminant before decoding
+fn filter_union_choice(val: &Value, map: &HashMap<String, Value>) -> Result<Value> {
+    let union = from_value::<LinkedUnion>(val.clone())?;
+    let name = map
+        .get("name")
+        .and_then(|val| val.as_str())
+        .ok_or_else(|| TeraError::msg("union_choice requires a name"))?;
+    let discriminants = union
+        .variants
+        .iter()
+        .map(|LinkedKeyVal(_, node, ..)| match node {
+            LinkedNode::Fields(fields) => fields
+                .members
+                .iter()
+                .find(|LinkedKeyVal(key, ..)| key == &union.discriminant)
+                .and_then(|LinkedKeyVal(_, node, wire)| match node {
+                    LinkedNode::Literal(lit) => Some((wire.clone(), lit.clone())),
+                    _ => None,
+                }),
+            _ => None,
+        })
+        .collect::<Option<Vec<(Option<Literal>, Literal)>>>()
+        .ok_or_else(|| TeraError::msg(format!("union {} is missing a discriminant", name)))?;
+    let reader = match discriminants.first() {
+        Some((_, Literal::Str(_))) => "str",
+        _ if discriminants
+            .iter()
+            .any(|(_, l)| matches!(l, Literal::Int(_))) =>
+        {
+            "i64"
+        }
+        _ => "u64",
+    };
+    // Every variant is a map, so we look for the entry of the discriminant by it's key
+    let wire = discriminants.first().and_then(|(wire, _)| wire.clone());
+    let test = match wire {
+        Some(Literal::UInt(_) | Literal::Int(_)) => format!(
+            "{} => i128::from(probe.int()?) == {},",
+            INT_KEY_TYPES,
+            key_pattern(&union.discriminant, &wire)
+        ),
+        _ => format!(
+            "minicbor::data::Type::String => probe.str()? == {},",
+            key_pattern(&union.discriminant, &wire)
+        ),
+    };
+    let probe = format!(
+        r#"let discriminant = {{
+            let mut probe = d.probe();
+            let len = probe.map()?.unwrap_or_default();
+            let mut discriminant = None;
+            for _ in 0..len {{
+                let found = match probe.datatype()? {{
+                    {}
+                    _ => {{
+                        probe.skip()?;
+                        false
+                    }}
+                }};
+                if found {{
+                    discriminant = Some(probe.{}()?);
+                    break;
+                }}
+                probe.skip()?;
+            }}
+            discriminant.ok_or_else(|| {{
+                minicbor::decode::Error::message("missing {} discriminant").at(p)
+            }})?
+        }};"#,
+        test, reader, union.discriminant
+    );
+    let variants = union
+        .variants
+        .iter()
+        .zip(discriminants.iter())
+        .map(|(LinkedKeyVal(key, fields, ..), (_, lit))| {
+            // Variants named after a rule such as "message-reboot" need not repeat the parent
+            let key = key
+                .strip_prefix(name)
+                .and_then(|key| key.strip_prefix('-'))
+                .unwrap_or(key);
+            let node = LinkedNode::ForeignStruct(format!("{}-{}", name, key));
+            let value = match lit {
+                Literal::Str(s) => format!("{:?}", s),
+                Literal::Int(i) => i.to_string(),
+                Literal::UInt(u) => u.to_string(),
+                Literal::Bool(b) => b.to_string(),
+                Literal::Bytes(_) | Literal::Null => unreachable!(),
+            };
+            json!({ "key": key, "val": node, "value": value, "fields": fields })
+        })
+        .collect::<Vec<Value>>();
+    Ok(json!({ "variants": variants, "probe": probe }))
+}
+
+/// Some types are not encoded with their minicbor impls. We return the module a field should
+/// be encoded "with" or an empty string if the field uses it's own Encode/Decode impls
+fn filter_with(val: &Value, _map: &HashMap<String, Value>) -> Result<Value> {
+    let LinkedKeyVal(_key, val, ..) = from_value::<LinkedKeyVal>(val.clone())?;
+    Ok(Value::String(
+        codec_with(&val).unwrap_or_default().to_string(),
+    ))
+}
+
+/// An array of u8 is a byte string
+fn is_bytes(arr: &LinkedArray) -> bool {
+    *unaliased(&arr.ty) == LinkedNode::ConstrainedType(ConstrainedType::U8)
+}
+
+/// Other arrays are a CBOR array of their entries, and are encoded, decoded and measured by an
+/// ArrayCodec. Strings inside an array are text strings, arrays of bytes are byte strings, and
+/// nested arrays are nested CBOR arrays
+fn array_codec(arr: &LinkedArray) -> String {
+    format!("__bindgen::Entries<{}>", entry_codec(&arr.ty))
+}
+
+/// The ArrayCodec of a value inside of an array or behind a tag
+fn entry_codec(node: &LinkedNode) -> String {
+    match unaliased(node) {
+        LinkedNode::ConstrainedType(ConstrainedType::Str(_)) => "__bindgen::TextString".to_string(),
+        LinkedNode::ConstrainedType(ConstrainedType::BStr) => "__bindgen::ByteString".to_string(),
+        LinkedNode::Array(arr) if is_bytes(arr) => "__bindgen::ByteString".to_string(),
+        LinkedNode::ConstrainedType(ConstrainedType::F16) => "__bindgen::Half".to_string(),
+        LinkedNode::Array(arr) => array_codec(arr),
+        LinkedNode::Tagged(tagged) => tagged_codec(tagged),
+        _ => "__bindgen::Entry".to_string(),
+    }
+}
+
+/// A tagged value is written after it's tag, and is encoded, decoded and measured by an
+/// ArrayCodec that checks the tag
+fn tagged_codec(tagged: &LinkedTagged) -> String {
+    format!(
+        "__bindgen::Tagged<{}, {}>",
+        tagged.tag,
+        entry_codec(&tagged.ty)
+    )
+}
+
+/// The path of a function of an ArrayCodec
+fn codec_fn(codec: &str, f: &str) -> String {
+    format!("<{} as __bindgen::ArrayCodec<_, _>>::{}", codec, f)
+}
+
+/// The path of a function of the ArrayCodec of an array
+fn array_fn(arr: &LinkedArray, f: &str) -> String {
+    codec_fn(&array_codec(arr), f)
+}
+
+fn array_attr(n: i64, arr: &LinkedArray) -> String {
+    codecs_attr(n, &array_codec(arr))
+}
+
+fn codecs_attr(n: i64, codec: &str) -> String {
+    format!(
+        r#"#[cbor(n({}), encode_with = "{}", decode_with = "{}", cbor_len = "{}")]"#,
+        n,
+        codec_fn(codec, "encode"),
+        codec_fn(codec, "decode"),
+        codec_fn(codec, "cbor_len")
+    )
+}
+
+/// Serde supports arrays of up to 32 entries, and strings are stored as bytes. Larger arrays are
+/// supported by BigArray, unless they are nested
+fn array_serde_attr(arr: &LinkedArray) -> Result<&'static str> {
+    match unaliased(&arr.ty) {
+        LinkedNode::Array(inner) if inner.len > 32 => Err(TeraError::msg(
+            "nested arrays larger than 32 are unsupported",
+        )),
+        LinkedNode::Array(inner) => array_serde_attr(inner).map(|_| match arr.len {
+            len if len > 32 => r#"#[serde(with="BigArray")]"#,
+            _ => "",
+        }),
+        LinkedNode::ConstrainedType(ConstrainedType::Str(_)) => Ok(
+            r#"#[serde(serialize_with = "__bindgen::ser_bytes_array_as_strs")]
+               #[serde(deserialize_with = "__bindgen::de_strs_as_bytes_array")]"#,
+        ),
+        _ if arr.len > 32 => Ok(r#"#[serde(with="BigArray")]"#),
+        _ => Ok(""),
+    }
+}
+
+fn codec_with(node: &LinkedNode) -> Option<&'static str> {
+    match unaliased(node) {
+        LinkedNode::ConstrainedType(ConstrainedType::Str(_)) => Some("__bindgen::text"),
+        LinkedNode::ConstrainedType(ConstrainedType::BStr) => Some("minicbor::bytes"),
+        LinkedNode::ConstrainedType(ConstrainedType::F16) => Some("__bindgen::float16"),
+        LinkedNode::Array(arr) if is_bytes(arr) => Some("minicbor::bytes"),
+        _ => None,
+    }
+}
+
+/// The attributes of a member are those of the type it's alias names, and an optional alias is
+/// an optional type
+fn member_unaliased(node: LinkedNode) -> LinkedNode {
+    match node {
+        LinkedNode::Alias(LinkedAlias { ty, .. }) => member_unaliased(*ty),
+        LinkedNode::Default(LinkedDefault { ty, .. }) => member_unaliased(*ty),
+        LinkedNode::Optional(node) => LinkedNode::Optional(Box::new(member_unaliased(*node))),
+        node => node,
+    }
+}
+
 fn filter_field_attr(val: &Value, map: &HashMap<String, Value>) -> Result<Value> {
     let lang = map
         .get("language")
         .and_then(|val| from_value::<String>(val.clone()).ok())
         .unwrap_or("c".to_string());
-    match lang.as_ref() {
-        "c" => filter_field_attr_c(val, map),
-        _ => filter_field_attr_rust(val, map),
+    let codec = map
+        .get("codec")
+        .and_then(|val| val.as_str())
+        .unwrap_or("derive");
+    // A map is indexed by the key of each member rather than it's position
+    if codec == "derive-map" {
+        let LinkedKeyVal(_, _, wire) = from_value::<LinkedKeyVal>(val.clone())?;
+        let mut map = map.clone();
+        match wire {
+            Some(Literal::UInt(n)) => map.insert("index".into(), Value::from(n)),
+            _ => return Err(TeraError::msg("derive-map requires integer keys")),
+        };
+        return match lang.as_ref() {
+            "c" => filter_field_attr_c(val, &map),
+            _ => filter_field_attr_rust(val, &map),
+        };
+    }
+    match (lang.as_ref(), codec) {
+        ("c", "manual" | "map") => Ok(Value::String("".into())),
+        (_, "manual" | "map") => filter_field_attr_serde(val),
+        ("c", _) => filter_field_attr_c(val, map),
+        (_, _) => filter_field_attr_rust(val, map),
     }
 }
 
+/// When a struct implements it's own Encode/Decode, the fields only carry serde attributes
+fn filter_field_attr_serde(val: &Value) -> Result<Value> {
+    let LinkedKeyVal(key, val, ..) = from_value::<LinkedKeyVal>(val.clone())?;
+    match member_unaliased(val) {
+        // The tag is not serialized
+        LinkedNode::Tagged(LinkedTagged { ty, .. }) => {
+            return filter_field_attr_serde(&to_value(LinkedKeyVal::new(key, *ty))?)
+        }
+        LinkedNode::Optional(node) => match *node {
+            LinkedNode::Tagged(LinkedTagged { ty, .. }) => {
+                let node = LinkedKeyVal::new(key, LinkedNode::Optional(ty));
+                return filter_field_attr_serde(&to_value(node)?);
+            }
+            LinkedNode::Array(LinkedArray { len, .. }) if len > 32 => Ok(r#"#[serde(default)]
+               #[serde(serialize_with = "__bindgen::ser_opt_big_array")]
+               #[serde(deserialize_with = "__bindgen::de_opt_big_array")]"#),
+            LinkedNode::ConstrainedType(ConstrainedType::Str(_)) => Ok(r#"#[serde(default)]
+               #[serde(serialize_with = "ser_opt_bytes_as_str")]
+               #[serde(deserialize_with = "de_opt_str_as_bytes")]"#),
+            _ => Ok(""),
+        },
+        LinkedNode::Array(arr) => array_serde_attr(&arr),
+        LinkedNode::ConstrainedType(ConstrainedType::Str(_)) => {
+            Ok(r#"#[serde(serialize_with = "ser_bytes_as_str")] 
+               #[serde(deserialize_with = "de_str_as_bytes")]"#)
+        }
+        _ => Ok(""),
+    }
+    .map(|attr| Value::String(attr.into()))
+}
+
 fn filter_field_attr_rust(val: &Value, map: &HashMap<String, Value>) -> Result<Value> {
-    let LinkedKeyVal(_key, val) = from_value::<LinkedKeyVal>(val.clone())?;
+    let LinkedKeyVal(_key, node, ..) = from_value::<LinkedKeyVal>(val.clone())?;
     map.get("index")
         .and_then(|i| i.as_i64())
-        .ok_or_else(|| TeraError::msg(format!("expected number")))
-        .map(|n| match val {
-            LinkedNode::Array(LinkedArray { ty, len }) => match *ty {
-                LinkedNode::ConstrainedType(ConstrainedType::U8) if len <= 32 => {
-                    Value::String(format!(r#"#[cbor(n({}), with = "minicbor::bytes")] "#, n))
-                }
-                LinkedNode::ConstrainedType(ConstrainedType::U8) if len > 32 => {
-                    Value::String(format!(
-                        r#"#[cbor(n({}), with = "minicbor::bytes")]
-                           #[serde(with="BigArray")]"#,
-                        n
-                    ))
-                }
-                _ => Value::String(format!("#[n({})]", n)),
+        .ok_or_else(|| TeraError::msg("expected number"))
+        .and_then(|n| match member_unaliased(node) {
+            LinkedNode::Optional(node) => match *node {
+                LinkedNode::Tagged(tagged) => tagged_attr(
+                    n,
+                    &format!("__bindgen::Optional<{}>", tagged_codec(&tagged)),
+                    val,
+                ),
+                node => optional_attr(n, &node).map(Value::String),
             },
-            LinkedNode::ConstrainedType(ConstrainedType::Str(_)) => Value::String(format!(
-                r#"#[cbor(n({}), with = "minicbor::bytes")] 
+            LinkedNode::Array(arr) if !is_bytes(&arr) => array_serde_attr(&arr)
+                .map(|serde| Value::String(format!("{}\n{}", array_attr(n, &arr), serde))),
+            LinkedNode::Tagged(tagged) => tagged_attr(n, &tagged_codec(&tagged), val),
+            node => Ok(field_attr_rust(n, node)),
+        })
+}
+
+/// A tagged member is encoded with the codec of it's tag, and is serialized like the type
+/// behind the tag
+fn tagged_attr(n: i64, codec: &str, val: &Value) -> Result<Value> {
+    filter_field_attr_serde(val).map(|serde| {
+        Value::String(format!(
+            "{}\n{}",
+            codecs_attr(n, codec),
+            serde.as_str().unwrap_or_default()
+        ))
+    })
+}
+
+/// An optional member is omitted when it is None. The derive macros treat a missing Option as
+/// None, even when the member is decoded with a custom codec
+fn optional_attr(n: i64, node: &LinkedNode) -> Result<String> {
+    match node {
+        LinkedNode::Range(range) => Ok(format!(
+            r#"#[cbor(n({}), decode_with = "{}")]"#,
+            n,
+            range_decoder(range).replace("decode_range", "decode_range_opt")
+        )),
+        LinkedNode::Array(arr) if !is_bytes(arr) => Err(TeraError::msg(
+            "optional arrays are only supported for bytes",
+        )),
+        LinkedNode::Array(LinkedArray { len, .. }) if *len > 32 => Ok(format!(
+            r#"{}
+               #[serde(default)]
+               #[serde(serialize_with = "__bindgen::ser_opt_big_array")]
+               #[serde(deserialize_with = "__bindgen::de_opt_big_array")]"#,
+            codec_attr(n, node)
+        )),
+        LinkedNode::Vector(vec) => Ok(format!(
+            r#"#[cbor(n({}), encode_with = "__bindgen::encode_opt_vec", decode_with = "{}", cbor_len = "__bindgen::cbor_len_opt_vec")]"#,
+            n,
+            vector_decoder("__bindgen::decode_opt_vec", vec)
+        )),
+        LinkedNode::ConstrainedType(ConstrainedType::Str(_)) => Ok(format!(
+            r#"#[cbor(n({}), with = "__bindgen::text")]
+               #[serde(default)]
+               #[serde(serialize_with = "ser_opt_bytes_as_str")]
+               #[serde(deserialize_with = "de_opt_str_as_bytes")]"#,
+            n
+        )),
+        node => Ok(codec_attr(n, node)),
+    }
+}
+
+/// The index of a member, with the codec of it's type when it isn't encoded by derive
+fn codec_attr(n: i64, node: &LinkedNode) -> String {
+    match codec_with(node) {
+        Some(with) => format!(r#"#[cbor(n({}), with = "{}")]"#, n, with),
+        None => format!("#[n({})]", n),
+    }
+}
+
+fn field_attr_rust(n: i64, val: LinkedNode) -> Value {
+    match val {
+        LinkedNode::Range(range) => Value::String(range_attr(n, &range)),
+        LinkedNode::Vector(vec) => Value::String(format!(
+            r#"#[cbor(n({}), encode_with = "__bindgen::encode_vec", decode_with = "{}", cbor_len = "__bindgen::cbor_len_vec")]"#,
+            n,
+            vector_decoder("__bindgen::decode_vec", &vec)
+        )),
+        LinkedNode::Array(LinkedArray { len, .. }) if len <= 32 => {
+            Value::String(format!(r#"#[cbor(n({}), with = "minicbor::bytes")] "#, n))
+        }
+        LinkedNode::Array(_) => Value::String(format!(
+            r#"#[cbor(n({}), with = "minicbor::bytes")]
+                           #[serde(with="BigArray")]"#,
+            n
+        )),
+        LinkedNode::ConstrainedType(ConstrainedType::Str(_)) => Value::String(format!(
+            r#"#[cbor(n({}), with = "__bindgen::text")] 
                    #[serde(serialize_with = "ser_bytes_as_str")] 
                    #[serde(deserialize_with = "de_str_as_bytes")]"#,
-                n
-            )),
-            _ => Value::String(format!("#[n({})]", n)),
-        })
+            n
+        )),
+        val => match codec_with(&val) {
+            Some(with) => Value::String(format!(r#"#[cbor(n({}), with = "{}")]"#, n, with)),
+            None => Value::String(format!("#[n({})]", n)),
+        },
+    }
 }
 
 fn filter_field_attr_c(val: &Value, map: &HashMap<String, Value>) -> Result<Value> {
-    let LinkedKeyVal(_key, val) = from_value::<LinkedKeyVal>(val.clone())?;
+    let LinkedKeyVal(_key, val, ..) = from_value::<LinkedKeyVal>(val.clone())?;
     map.get("index")
         .and_then(|i| i.as_i64())
         .ok_or_else(|| TeraError::msg(format!("expected number")))
-        .map(|n| match val {
-            LinkedNode::Array(LinkedArray { ty, .. }) => match *ty {
-                LinkedNode::ConstrainedType(ConstrainedType::U8) => {
-                    Value::String(format!(r#"#[cbor(n({}), with = "minicbor::bytes")] "#, n))
-                }
-                _ => Value::String(format!("#[n({})]", n)),
+        .map(|n| match member_unaliased(val) {
+            LinkedNode::Range(range) => Value::String(range_attr(n, &range)),
+            LinkedNode::Array(arr) if is_bytes(&arr) => {
+                Value::String(format!(r#"#[cbor(n({}), with = "minicbor::bytes")] "#, n))
+            }
+            LinkedNode::Array(arr) => Value::String(array_attr(n, &arr)),
+            LinkedNode::Tagged(tagged) => Value::String(codecs_attr(n, &tagged_codec(&tagged))),
+            val => match codec_with(&val) {
+                Some(with) => Value::String(format!(r#"#[cbor(n({}), with = "{}")]"#, n, with)),
+                None => Value::String(format!("#[n({})]", n)),
             },
-            LinkedNode::ConstrainedType(ConstrainedType::Str(_)) => {
-                Value::String(format!(r#"#[cbor(n({}), with = "minicbor::bytes")]"#, n))
+        })
+}
+
+/// A range is decoded with a bounds check
+fn range_attr(n: i64, range: &LinkedRange) -> String {
+    format!(
+        r#"#[cbor(n({}), decode_with = "{}")]"#,
+        n,
+        range_decoder(range)
+    )
+}
+
+/// A vector is decoded with a check of it's number of entries
+fn vector_decoder(f: &str, vec: &LinkedVector) -> String {
+    format!("{}::<_, _, {}, {}>", f, vec.min, vec.max)
+}
+
+fn range_decoder(range: &LinkedRange) -> String {
+    match &range.ne {
+        Some(ne) => format!(
+            "__bindgen::decode_range_except::<_, _, {}, {}, {}>",
+            const_arg(&range.min),
+            const_arg(&range.max),
+            const_arg(ne)
+        ),
+        None => format!(
+            "__bindgen::decode_range::<_, _, {}, {}>",
+            const_arg(&range.min),
+            const_arg(&range.max)
+        ),
+    }
+}
+
+/// Negative const generic arguments must be wrapped in braces
+fn const_arg(lit: &Literal) -> String {
+    match lit.as_int() {
+        Some(val) if val < 0 => format!("{{ {} }}", val),
+        _ => literal_int(lit),
+    }
+}
+
+fn range_contains(min: &Literal, max: &Literal, val: i128) -> bool {
+    match (min.as_int(), max.as_int()) {
+        (Some(min), Some(max)) => (min..=max).contains(&val),
+        _ => false,
+    }
+}
+
+/// Render an integer literal without a type suffix
+fn literal_int(lit: &Literal) -> String {
+    lit.as_int().map(|val| val.to_string()).unwrap_or_default()
+}
+
+/// Render the expression of a condition that must hold for a member to be valid, or an empty
+/// string if the member is always valid
+fn filter_validate(val: &Value, map: &HashMap<String, Value>) -> Result<Value> {
+    let LinkedKeyVal(key, node, ..) = from_value::<LinkedKeyVal>(val.clone())?;
+    let expr = member_expr(&key, map);
+    match node {
+        LinkedNode::Range(LinkedRange { min, max, ty, ne }) => {
+            let ty = rust_type(&LinkedNode::ConstrainedType(ty), map)?;
+            let ne = match ne {
+                Some(ne) => format!(" && {} != {}{}", expr, literal_int(&ne), ty),
+                None => String::new(),
+            };
+            Ok(format!(
+                "({}{ty}..={}{ty}).contains(&{}){}",
+                literal_int(&min),
+                literal_int(&max),
+                expr,
+                ne,
+                ty = ty
+            ))
+        }
+        LinkedNode::Optional(node) => {
+            // The presence flag is a sibling of the member
+            let has = match expr.rsplit_once('.') {
+                Some((parent, field)) => format!("{}.has_{}", parent, field),
+                None => format!("has_{}", expr),
+            };
+            let node = LinkedKeyVal::new(key, *node);
+            let check = filter_validate(&to_value(node)?, map)?;
+            match check.as_str() {
+                Some("") | None => Ok("".into()),
+                Some(check) => Ok(format!("(!{} || {})", has, check)),
             }
-            _ => Value::String(format!("#[n({})]", n)),
+        }
+        LinkedNode::Vector(LinkedVector { min, max, .. }) => {
+            Ok(format!("({}u32..={}u32).contains(&{}_len)", min, max, expr))
+        }
+        LinkedNode::Tagged(LinkedTagged { ty, .. })
+        | LinkedNode::Alias(LinkedAlias { ty, .. })
+        | LinkedNode::Default(LinkedDefault { ty, .. }) => {
+            return filter_validate(&to_value(LinkedKeyVal::new(key, *ty))?, map);
+        }
+        LinkedNode::ForeignStruct(name) => caseify(&name, "fn", &{
+            let mut map = map.clone();
+            map.insert("verb".into(), Value::String("validate".into()));
+            map
         })
+        .map(|f| format!("{}(&{})", f, expr)),
+        _ => Ok("".into()),
+    }
+    .map(Value::String)
 }
 
 /// Take a field node and convert to a field member according to lang type
@@ -320,52 +1904,83 @@ fn filter_field(val: &Value, map: &HashMap<String, Value>) -> Result<Value> {
 
 /// Take a field node and convert to a field member for rust struct
 fn filter_field_rs(val: &Value, map: &HashMap<String, Value>) -> Result<Value> {
-    let LinkedKeyVal(key, val) = from_value::<LinkedKeyVal>(val.clone())
-        .map(|LinkedKeyVal(key, val)| LinkedKeyVal(key.to_snake_case(), val))?;
-    match val {
-        LinkedNode::ConstrainedType(ConstrainedType::U8) => Ok(format!("pub {}: u8", key)),
-        LinkedNode::ConstrainedType(ConstrainedType::U16) => Ok(format!("pub {}: u16", key)),
-        LinkedNode::ConstrainedType(ConstrainedType::U32) => Ok(format!("pub {}: u32", key)),
-        LinkedNode::ConstrainedType(ConstrainedType::U64) => Ok(format!("pub {}: u64", key)),
-        LinkedNode::ConstrainedType(ConstrainedType::I8) => Ok(format!("pub {}: i8", key)),
-        LinkedNode::ConstrainedType(ConstrainedType::I16) => Ok(format!("pub {}: i16", key)),
-        LinkedNode::ConstrainedType(ConstrainedType::I32) => Ok(format!("pub {}: i32", key)),
-        LinkedNode::ConstrainedType(ConstrainedType::I64) => Ok(format!("pub {}: i64", key)),
-        LinkedNode::ConstrainedType(ConstrainedType::Bool) => Ok(format!("pub {}: bool", key)),
-        LinkedNode::ConstrainedType(ConstrainedType::Str(n)) => Ok(field_arr!(key, "u8", n)),
-        LinkedNode::ForeignStruct(s) => Ok(format!("pub {}: {}", key, caseify(&s, "struct", map)?)),
-        LinkedNode::Array(LinkedArray { ty, len }) => match *ty {
-            LinkedNode::ConstrainedType(ConstrainedType::U8) => Ok(field_arr!(key, "u8", len)),
-            _ => unimplemented!(),
-        },
-        _ => unimplemented!(),
+    let LinkedKeyVal(key, val, ..) = from_value::<LinkedKeyVal>(val.clone())
+        .map(|LinkedKeyVal(key, val, wire)| LinkedKeyVal(key.to_snake_case(), val, wire))?;
+    match (val, language(map)) {
+        // A C struct cannot hold an Option, so an optional member is paired with a presence flag
+        (LinkedNode::Optional(val), Language::C) => {
+            let member = to_value(LinkedKeyVal::new(key.clone(), *val))?;
+            filter_field_rs(&member, map).map(|field| {
+                Value::String(format!(
+                    "pub has_{}: bool, {}",
+                    key,
+                    field.as_str().unwrap_or("")
+                ))
+            })
+        }
+        // A C struct cannot hold a Vec, so a vector is stored in an array paired with a length
+        (val @ LinkedNode::Vector(_), Language::C) => rust_type(&val, map).map(|ty| {
+            Value::String(format!(
+                "pub {key}: {ty}, pub {key}_len: u32",
+                key = key,
+                ty = ty
+            ))
+        }),
+        (val, _) => rust_type(&val, map).map(|ty| Value::String(format!("pub {}: {}", key, ty))),
     }
-    .map(Value::String)
 }
 
 /// NOTE This is identical to the rust struct except the fields are not public
 fn filter_field_ts(val: &Value, map: &HashMap<String, Value>) -> Result<Value> {
-    let LinkedKeyVal(key, val) = from_value::<LinkedKeyVal>(val.clone())
-        .map(|LinkedKeyVal(key, val)| LinkedKeyVal(key.to_snake_case(), val))?;
-    match val {
-        LinkedNode::ConstrainedType(ConstrainedType::U8) => Ok(format!("{}: u8", key)),
-        LinkedNode::ConstrainedType(ConstrainedType::U16) => Ok(format!("{}: u16", key)),
-        LinkedNode::ConstrainedType(ConstrainedType::U32) => Ok(format!("{}: u32", key)),
-        LinkedNode::ConstrainedType(ConstrainedType::U64) => Ok(format!("{}: u64", key)),
-        LinkedNode::ConstrainedType(ConstrainedType::I8) => Ok(format!("{}: i8", key)),
-        LinkedNode::ConstrainedType(ConstrainedType::I16) => Ok(format!("{}: i16", key)),
-        LinkedNode::ConstrainedType(ConstrainedType::I32) => Ok(format!("{}: i32", key)),
-        LinkedNode::ConstrainedType(ConstrainedType::I64) => Ok(format!("{}: i64", key)),
-        LinkedNode::ConstrainedType(ConstrainedType::Bool) => Ok(format!("{}: bool", key)),
-        LinkedNode::ConstrainedType(ConstrainedType::Str(n)) => Ok(field_arr!(key, "u8", n)),
-        LinkedNode::ForeignStruct(s) => Ok(format!("{}: {}", key, caseify(&s, "struct", map)?)),
-        LinkedNode::Array(LinkedArray { ty, len }) => match *ty {
-            LinkedNode::ConstrainedType(ConstrainedType::U8) => Ok(field_arr!(key, "u8", len)),
-            _ => unimplemented!(),
+    let LinkedKeyVal(key, val, ..) = from_value::<LinkedKeyVal>(val.clone())
+        .map(|LinkedKeyVal(key, val, wire)| LinkedKeyVal(key.to_snake_case(), val, wire))?;
+    rust_type(&val, map).map(|ty| Value::String(format!("{}: {}", key, ty)))
+}
+
+/// Similar to field, but only the type (useful for tuple variants)
+fn filter_ty(val: &Value, map: &HashMap<String, Value>) -> Result<Value> {
+    let LinkedKeyVal(_key, val, ..) = from_value::<LinkedKeyVal>(val.clone())?;
+    rust_type(&val, map).map(Value::String)
+}
+
+/// Take a linked node and render the rust type that stores it
+fn rust_type(node: &LinkedNode, map: &HashMap<String, Value>) -> Result<String> {
+    match node {
+        LinkedNode::ConstrainedType(ConstrainedType::U8) => Ok("u8".into()),
+        LinkedNode::ConstrainedType(ConstrainedType::U16) => Ok("u16".into()),
+        LinkedNode::ConstrainedType(ConstrainedType::U32) => Ok("u32".into()),
+        LinkedNode::ConstrainedType(ConstrainedType::U64) => Ok("u64".into()),
+        LinkedNode::ConstrainedType(ConstrainedType::I8) => Ok("i8".into()),
+        LinkedNode::ConstrainedType(ConstrainedType::I16) => Ok("i16".into()),
+        LinkedNode::ConstrainedType(ConstrainedType::I32) => Ok("i32".into()),
+        LinkedNode::ConstrainedType(ConstrainedType::I64) => Ok("i64".into()),
+        LinkedNode::ConstrainedType(ConstrainedType::Bool) => Ok("bool".into()),
+        LinkedNode::ConstrainedType(ConstrainedType::F16) => Ok("f32".into()),
+        LinkedNode::ConstrainedType(ConstrainedType::F32) => Ok("f32".into()),
+        LinkedNode::ConstrainedType(ConstrainedType::F64) => Ok("f64".into()),
+        LinkedNode::ConstrainedType(ConstrainedType::Str(n)) => Ok(format!("[u8; {}]", n)),
+        LinkedNode::ConstrainedType(ConstrainedType::Int) => Ok("i64".into()),
+        LinkedNode::ConstrainedType(ConstrainedType::UInt) => Ok("u64".into()),
+        LinkedNode::ConstrainedType(ConstrainedType::TStr) => Ok("String".into()),
+        LinkedNode::ConstrainedType(ConstrainedType::BStr) => Ok("Vec<u8>".into()),
+        LinkedNode::ConstrainedType(ConstrainedType::Any) => Ok("__bindgen::AnyCbor".into()),
+        LinkedNode::ForeignStruct(s) => caseify(s, "struct", map),
+        LinkedNode::ForeignChoice(s) => caseify(s, "struct", map),
+        LinkedNode::Alias(LinkedAlias { name, .. }) => caseify(name, "struct", map),
+        LinkedNode::Default(LinkedDefault { ty, .. }) => rust_type(ty, map),
+        LinkedNode::Range(range) => rust_type(&LinkedNode::ConstrainedType(range.ty.clone()), map),
+        LinkedNode::Optional(node) => rust_type(node, map).map(|ty| format!("Option<{}>", ty)),
+        LinkedNode::Boxed(node) => rust_type(node, map).map(|ty| format!("Box<{}>", ty)),
+        LinkedNode::Vector(LinkedVector { ty, max, .. }) => match language(map) {
+            Language::C => rust_type(ty, map).map(|ty| format!("[{}; {}]", ty, max)),
+            _ => rust_type(ty, map).map(|ty| format!("heapless::Vec<{}, {}>", ty, max)),
         },
-        _ => unimplemented!(),
+        LinkedNode::Array(LinkedArray { ty, len }) => {
+            rust_type(ty, map).map(|ty| format!("[{}; {}]", ty, len))
+        }
+        LinkedNode::Tagged(LinkedTagged { ty, .. }) => rust_type(ty, map),
+        node => Err(TeraError::msg(format!("unsupported type {:?}", node))),
     }
-    .map(Value::String)
 }
 
 macro_rules! fn_attr {
@@ -390,7 +2005,7 @@ fn filter_fn_attr(val: &Value, _map: &HashMap<String, Value>) -> Result<Value> {
 }
 
 macro_rules! wasm_copyable_impl {
-    ($key:expr, $ty: literal) => {{
+    ($key:expr, $ty: expr) => {{
         let camel = $key.to_lower_camel_case();
         let snake = $key.to_snake_case();
         let getter = format!("self.{}", snake);
@@ -418,6 +2033,36 @@ macro_rules! wasm_clonable_impl {
     }};
 }
 
+/// Like wasm_clonable_impl, but the type is not one of our structs
+macro_rules! wasm_owned_impl {
+    ($key:expr, $ty: expr) => {{
+        let camel = $key.to_lower_camel_case();
+        let snake = $key.to_snake_case();
+        let getter = format!("self.{}.clone()", snake);
+        let setter = format!("self.{} = val", snake);
+        format!(
+            "{} {}",
+            wasm_impl_getter!(camel, snake, $ty, getter),
+            wasm_impl_setter!(camel, snake, $ty, setter)
+        )
+    }};
+}
+
+/// Any data item is exposed to javascript as it's encoded bytes
+macro_rules! wasm_any_impl {
+    ($key:expr) => {{
+        let camel = $key.to_lower_camel_case();
+        let snake = $key.to_snake_case();
+        let getter = format!("self.{}.0.clone()", snake);
+        let setter = format!("self.{} = __bindgen::AnyCbor(val)", snake);
+        format!(
+            "{} {}",
+            wasm_impl_getter!(camel, snake, "Vec<u8>", getter),
+            wasm_impl_setter!(camel, snake, "Vec<u8>", setter)
+        )
+    }};
+}
+
 macro_rules! wasm_str_getter {
     ($key:expr) => {
         format!(
@@ -457,7 +2102,7 @@ macro_rules! wasm_bytes_setter {
             r#"
             let min = core::cmp::min(val.len(), {len});
             self.{var}[0..min].copy_from_slice(&val[0..min]);
-            self.{var}[min..].fill(0);
+            self.{var}[min..].fill(Default::default());
             "#,
             len = $len,
             var = $key
@@ -493,6 +2138,115 @@ macro_rules! wasm_bytes_impl {
     }};
 }
 
+macro_rules! wasm_array_impl {
+    ($key:expr, $ty:expr, $len:expr) => {{
+        let camel = $key.to_lower_camel_case();
+        let snake = $key.to_snake_case();
+        let getter = wasm_bytes_getter!(snake);
+        let setter = wasm_bytes_setter!(snake, $len);
+        format!(
+            "{} {}",
+            wasm_impl_getter!(camel, snake, format!("Vec<{}>", $ty), getter),
+            wasm_impl_setter!(camel, snake, format!("&[{}]", $ty), setter)
+        )
+    }};
+}
+
+/// Serde only supports arrays of up to 32 entries, so the entries are passed as a slice
+macro_rules! wasm_slice_impl {
+    ($key:expr, $ty:expr, $len:expr) => {{
+        let camel = $key.to_lower_camel_case();
+        let snake = $key.to_snake_case();
+        let getter = format!("serde_wasm_bindgen::to_value(&self.{}[..]).unwrap()", snake);
+        let setter = format!(
+            r#"
+            let val: Vec<{ty}> = serde_wasm_bindgen::from_value(val)?;
+            if val.len() != {len} {{
+                return Err(JsValue::from_str("expected {len} {key} items"));
+            }}
+            self.{var}.clone_from_slice(&val);
+            Ok(())
+            "#,
+            ty = $ty,
+            len = $len,
+            key = $key,
+            var = snake
+        );
+        format!(
+            "{} {}",
+            wasm_impl_getter!(camel, snake, "JsValue", getter),
+            wasm_impl_try_setter!(camel, snake, "JsValue", setter)
+        )
+    }};
+}
+
+/// A vector is set from an array of any length within it's bounds, other lengths are thrown
+macro_rules! wasm_vec_impl {
+    ($key:expr, $ty:expr, $min:expr, $max:expr) => {{
+        let camel = $key.to_lower_camel_case();
+        let snake = $key.to_snake_case();
+        let getter = format!("self.{}.to_vec()", snake);
+        let setter = format!(
+            r#"
+            if !({min}..={max}).contains(&val.len()) {{
+                return Err(JsValue::from_str("expected {min} to {max} {key} items"));
+            }}
+            self.{var} = val.into_iter().collect();
+            Ok(())
+            "#,
+            min = $min,
+            max = $max,
+            key = $key,
+            var = snake
+        );
+        let ty = format!("Vec<{}>", $ty);
+        format!(
+            "{} {}",
+            wasm_impl_getter!(camel, snake, ty, getter),
+            wasm_impl_try_setter!(camel, snake, ty, setter)
+        )
+    }};
+}
+
+macro_rules! wasm_serde_impl {
+    ($key:expr) => {{
+        let camel = $key.to_lower_camel_case();
+        let snake = $key.to_snake_case();
+        let getter = format!("serde_wasm_bindgen::to_value(&self.{}).unwrap()", snake);
+        let setter = format!(
+            r#"self.{var} = serde_wasm_bindgen::from_value(val).expect("invalid {var}")"#,
+            var = snake
+        );
+        format!(
+            "{} {}",
+            wasm_impl_getter!(camel, snake, "JsValue", getter),
+            wasm_impl_setter!(camel, snake, "JsValue", setter)
+        )
+    }};
+}
+
+/// Optional arrays too large for serde go through the same BigArray helper as the struct
+macro_rules! wasm_opt_big_array_impl {
+    ($key:expr) => {{
+        let camel = $key.to_lower_camel_case();
+        let snake = $key.to_snake_case();
+        let getter = format!(
+            "__bindgen::ser_opt_big_array(&self.{}, &serde_wasm_bindgen::Serializer::new()).unwrap()",
+            snake
+        );
+        let setter = format!(
+            r#"self.{var} = __bindgen::de_opt_big_array(serde_wasm_bindgen::Deserializer::from(val))
+                .expect("invalid {var}")"#,
+            var = snake
+        );
+        format!(
+            "{} {}",
+            wasm_impl_getter!(camel, snake, "JsValue", getter),
+            wasm_impl_setter!(camel, snake, "JsValue", setter)
+        )
+    }};
+}
+
 macro_rules! wasm_impl_getter {
     ($camel:expr, $snake:expr, $ty:literal, $getter:expr) => {{
         let exp = $ty;
@@ -519,11 +2273,41 @@ macro_rules! wasm_impl_setter {
     };
 }
 
+/// The tag of a member is not visible to javascript, and an alias is the type it names
+fn untagged(node: LinkedNode) -> LinkedNode {
+    match node {
+        LinkedNode::Tagged(LinkedTagged { ty, .. }) => untagged(*ty),
+        LinkedNode::Alias(LinkedAlias { ty, .. }) => untagged(*ty),
+        LinkedNode::Default(LinkedDefault { ty, .. }) => untagged(*ty),
+        LinkedNode::Optional(node) => LinkedNode::Optional(Box::new(untagged(*node))),
+        LinkedNode::Array(LinkedArray { ty, len }) => LinkedNode::Array(LinkedArray {
+            ty: Box::new(untagged(*ty)),
+            len,
+        }),
+        LinkedNode::Vector(LinkedVector { ty, min, max }) => LinkedNode::Vector(LinkedVector {
+            ty: Box::new(untagged(*ty)),
+            min,
+            max,
+        }),
+        node => node,
+    }
+}
+
+/// Similar to wasm_impl_setter, but the setter throws when the value is invalid
+macro_rules! wasm_impl_try_setter {
+    ($camel:expr, $snake:expr, $ty:expr, $setter:expr) => {
+        format!(
+            r#"#[wasm_bindgen(setter, js_name={})] pub fn set_{}(&mut self, val: {}) -> Result<(), JsValue> {{ {} }}"#,
+            $camel, $snake, $ty, $setter
+        )
+    };
+}
+
 fn filter_wasm_member(val: &Value, _map: &HashMap<String, Value>) -> Result<Value> {
     use crate::ivt::ConstrainedType::*;
     use LinkedNode::*;
-    let LinkedKeyVal(key, val) = from_value::<LinkedKeyVal>(val.clone())?;
-    match val {
+    let LinkedKeyVal(key, val, ..) = from_value::<LinkedKeyVal>(val.clone())?;
+    match untagged(val) {
         ConstrainedType(U8) => Ok(Value::String(wasm_copyable_impl!(key, "u8"))),
         ConstrainedType(I8) => Ok(Value::String(wasm_copyable_impl!(key, "i8"))),
         ConstrainedType(U16) => Ok(Value::String(wasm_copyable_impl!(key, "u16"))),
@@ -533,12 +2317,211 @@ fn filter_wasm_member(val: &Value, _map: &HashMap<String, Value>) -> Result<Valu
         ConstrainedType(U64) => Ok(Value::String(wasm_copyable_impl!(key, "u64"))),
         ConstrainedType(I64) => Ok(Value::String(wasm_copyable_impl!(key, "i64"))),
         ConstrainedType(Bool) => Ok(Value::String(wasm_copyable_impl!(key, "bool"))),
+        ConstrainedType(F16) => Ok(Value::String(wasm_copyable_impl!(key, "f32"))),
+        ConstrainedType(F32) => Ok(Value::String(wasm_copyable_impl!(key, "f32"))),
+        ConstrainedType(F64) => Ok(Value::String(wasm_copyable_impl!(key, "f64"))),
         ConstrainedType(Str(len)) => Ok(Value::String(wasm_str_impl!(key, len))),
+        ConstrainedType(Int) => Ok(Value::String(wasm_copyable_impl!(key, "i64"))),
+        ConstrainedType(UInt) => Ok(Value::String(wasm_copyable_impl!(key, "u64"))),
+        ConstrainedType(TStr) => Ok(Value::String(wasm_owned_impl!(key, "String"))),
+        ConstrainedType(BStr) => Ok(Value::String(wasm_owned_impl!(key, "Vec<u8>"))),
+        ConstrainedType(Any) => Ok(Value::String(wasm_any_impl!(key))),
         ForeignStruct(s) => Ok(Value::String(wasm_clonable_impl!(key, s))),
+        ForeignChoice(_) => Ok(Value::String(wasm_serde_impl!(key))),
+        Range(range) => {
+            let node = LinkedKeyVal::new(key, ConstrainedType(range.ty));
+            filter_wasm_member(&to_value(node)?, _map)
+        }
+        Optional(node) => match *node {
+            ConstrainedType(Str(_))
+            | ConstrainedType(Bytes(_))
+            | ConstrainedType(TStr)
+            | ConstrainedType(BStr)
+            | ConstrainedType(Any) => Ok(Value::String(wasm_serde_impl!(key))),
+            Array(LinkedArray { len, .. }) if len > 32 => {
+                Ok(Value::String(wasm_opt_big_array_impl!(key)))
+            }
+            ConstrainedType(ty) => {
+                let ty = rust_type(&ConstrainedType(ty), _map)?;
+                Ok(Value::String(wasm_copyable_impl!(
+                    key,
+                    format!("Option<{}>", ty)
+                )))
+            }
+            Range(range) => {
+                let ty = rust_type(&ConstrainedType(range.ty), _map)?;
+                Ok(Value::String(wasm_copyable_impl!(
+                    key,
+                    format!("Option<{}>", ty)
+                )))
+            }
+            _ => Ok(Value::String(wasm_serde_impl!(key))),
+        },
         Array(LinkedArray { ty, len }) => match *ty {
             ConstrainedType(U8) => Ok(Value::String(wasm_bytes_impl!(key, len))),
-            _ => unimplemented!(),
+            ConstrainedType(Str(_))
+            | ConstrainedType(TStr)
+            | ConstrainedType(BStr)
+            | ConstrainedType(Any) => Ok(Value::String(wasm_serde_impl!(key))),
+            ConstrainedType(Bool) => {
+                let ty = rust_type(&ConstrainedType(Bool), _map)?;
+                Ok(Value::String(wasm_slice_impl!(key, ty, len)))
+            }
+            ConstrainedType(ty) => {
+                let ty = rust_type(&ConstrainedType(ty), _map)?;
+                Ok(Value::String(wasm_array_impl!(key, ty, len)))
+            }
+            Range(range) => {
+                let ty = rust_type(&ConstrainedType(range.ty), _map)?;
+                Ok(Value::String(wasm_array_impl!(key, ty, len)))
+            }
+            // Nested arrays are nested Array values
+            ty => {
+                let ty = rust_type(&ty, _map)?;
+                Ok(Value::String(wasm_slice_impl!(key, ty, len)))
+            }
+        },
+        Vector(LinkedVector { ty, min, max }) => match *ty {
+            ConstrainedType(Bool) | ConstrainedType(TStr) | ConstrainedType(Any) => {
+                Ok(Value::String(wasm_serde_impl!(key)))
+            }
+            ConstrainedType(ty) => {
+                let ty = rust_type(&ConstrainedType(ty), _map)?;
+                Ok(Value::String(wasm_vec_impl!(key, ty, min, max)))
+            }
+            Range(range) => {
+                let ty = rust_type(&ConstrainedType(range.ty), _map)?;
+                Ok(Value::String(wasm_vec_impl!(key, ty, min, max)))
+            }
+            _ => Ok(Value::String(wasm_serde_impl!(key))),
         },
         _ => Ok(Value::String("".into())),
     }
 }
+
+/// An alias is rendered like a struct of one member, so that the member filters render the type
+/// it names
+fn filter_alias_member(val: &Value, _map: &HashMap<String, Value>) -> Result<Value> {
+    let node = from_value::<LinkedNode>(val.clone())?;
+    Ok(to_value(LinkedKeyVal::new("0", node))?)
+}
+
+/// An array type is rendered like a struct of one member, which holds the items of the array
+fn filter_array_fields(val: &Value, _map: &HashMap<String, Value>) -> Result<Value> {
+    let node = from_value::<LinkedNode>(val.clone())?;
+    Ok(to_value(Fields {
+        members: vec![LinkedKeyVal::new("items", node)],
+    })?)
+}
+
+/// A dictionary is rendered as a struct that holds it's entries. In C the keys and the values are
+/// stored in arrays of it's capacity, paired with the number of entries. Otherwise the entries are
+/// stored in a LinearMap (the capacity of an FnvIndexMap must be a power of two, and it's keys
+/// must implement hash32), or in a BTreeMap when the dictionary is unbounded
+fn filter_dictionary(val: &Value, map: &HashMap<String, Value>) -> Result<Value> {
+    let dict = from_value::<LinkedDictionary>(val.clone())?;
+    let key = rust_type(&dict.key, map)?;
+    let value = rust_type(&dict.val, map)?;
+    // An entry is inserted when it's key is not present, and the capacity was checked already
+    let (ty, insert, max) = match dict.max {
+        usize::MAX => (
+            format!("std::collections::BTreeMap<{}, {}>", key, value),
+            "entries.insert(k, v).is_none()",
+            "{ usize::MAX }".to_string(),
+        ),
+        max => (
+            format!("heapless::LinearMap<{}, {}, {}>", key, value, max),
+            "matches!(entries.insert(k, v), Ok(None))",
+            max.to_string(),
+        ),
+    };
+    let codec = format!(
+        "__bindgen::MapEntries::<{}, {}, {}, {}>",
+        entry_codec(&dict.key),
+        entry_codec(&dict.val),
+        dict.min,
+        max
+    );
+    // Serde stores a text key as an array of bytes, which is not a valid key of a JSON object
+    let serde = match *dict.key {
+        LinkedNode::ConstrainedType(ConstrainedType::Str(n)) => format!(
+            r#"#[serde(serialize_with = "__bindgen::ser_str_keyed_map", deserialize_with = "__bindgen::de_str_keyed_map::<_, _, _, {}, {}>")]"#,
+            n, max
+        ),
+        _ => String::new(),
+    };
+    Ok(json!({
+        "key": key,
+        "value": value,
+        "ty": ty,
+        "insert": insert,
+        "codec": codec,
+        "serde": serde,
+        "key_init": format!("<{} as __bindgen::ArrayCodec<{}, ()>>::init", entry_codec(&dict.key), key),
+        "value_init": format!("<{} as __bindgen::ArrayCodec<{}, ()>>::init", entry_codec(&dict.val), value),
+        "min": dict.min,
+        "max": max,
+    }))
+}
+
+/// A .bits control is rendered as a newtype of it's integer, with an associated constant for each
+/// named bit
+fn filter_bits(val: &Value, map: &HashMap<String, Value>) -> Result<Value> {
+    let bits = from_value::<LinkedBits>(val.clone())?;
+    let ty = rust_type(&LinkedNode::ConstrainedType(bits.ty), map)?;
+    let flags = bits
+        .flags
+        .iter()
+        .map(|(name, bit)| json!({ "name": name.to_shouty_snake_case(), "bit": bit }))
+        .collect::<Vec<Value>>();
+    let all = bits.flags.iter().fold(0u64, |all, (_, bit)| all | 1 << bit);
+    Ok(json!({ "ty": ty, "flags": flags, "all": all }))
+}
+
+/// A .regexp control is rendered as a newtype of it's text, and the text is matched against the
+/// pattern when decoded (and validated, in C). The pattern is anchored, as CDDL matches the whole
+/// text
+fn filter_regexp(val: &Value, map: &HashMap<String, Value>) -> Result<Value> {
+    let regexp = from_value::<LinkedRegexp>(val.clone())?;
+    let name = map
+        .get("name")
+        .and_then(|val| val.as_str())
+        .ok_or_else(|| TeraError::msg("regexp requires a name"))?;
+    let pattern = caseify(&format!("{}-pattern", name), "const", map)?;
+    let matches = |expr: &str| match regexp.ty {
+        ConstrainedType::Str(_) => {
+            format!(
+                "{}.from_bytes().is_ok_and(|s| {}.is_match(s))",
+                expr, pattern
+            )
+        }
+        _ => format!("{}.is_match(&{})", pattern, expr),
+    };
+    Ok(json!({
+        "member": LinkedKeyVal::new("0", LinkedNode::ConstrainedType(regexp.ty.clone())),
+        "pattern": pattern,
+        "source": format!("^(?:{})$", regexp.pattern),
+        "decode": matches("val"),
+        "validate": matches("src.0"),
+    }))
+}
+
+/// An encoded value is rendered as a struct that holds it's bytes. In C the bytes are stored in an
+/// array of it's capacity, paired with the number of bytes. Otherwise the bytes are stored in a
+/// heapless Vec, or in a Vec when the byte string is unbounded
+fn filter_embedded(val: &Value, map: &HashMap<String, Value>) -> Result<Value> {
+    let embedded = from_value::<LinkedEmbedded>(val.clone())?;
+    let ty = match (language(map), embedded.max) {
+        (Language::C, max) => format!("[u8; {}]", max),
+        (_, usize::MAX) => "Vec<u8>".to_string(),
+        (_, max) => format!("heapless::Vec<u8, {}>", max),
+    };
+    Ok(json!({
+        "item": rust_type(&embedded.ty, map)?,
+        "ty": ty,
+        "max": embedded.max,
+        "bounded": embedded.max != usize::MAX,
+        "seq": embedded.seq,
+        "tag": embedded.tag,
+    }))
+}
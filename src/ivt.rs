@@ -18,6 +18,12 @@ enum PrimativeType {
     TStr,
     /// The CDDL primative "bool" type
     Bool,
+    /// The CDDL primative "float16" type
+    Float16,
+    /// The CDDL primative "float32" (or "float16-32") type
+    Float32,
+    /// The CDDL primative "float64" (or "float32-64", "float") type
+    Float64,
     /// A CDDL type defined in another rule further in the ruleset
     Unresolved(String),
 }
@@ -32,10 +38,29 @@ pub enum Literal {
     Bool(bool),
     /// A CDDL literal string, AKA "Site"
     Str(String),
-    /// A CDDL literal char, AKA 'G'
-    Char(char),
-    /// A CDDL literal byte array AKA [3,2,1]
+    /// A CDDL literal byte string, AKA 'Site' or h'030201'
     Bytes(Vec<u8>),
+    /// The CDDL null (or nil) value from the prelude
+    Null,
+}
+
+impl Literal {
+    /// An integer literal, which is a UInt unless the value is negative
+    pub(crate) fn from_int(val: i128) -> Literal {
+        match val {
+            0.. => Literal::UInt(val as u64),
+            _ => Literal::Int(val as i64),
+        }
+    }
+
+    /// The value of an integer literal
+    pub(crate) fn as_int(&self) -> Option<i128> {
+        match self {
+            Literal::UInt(u) => Some(*u as i128),
+            Literal::Int(i) => Some(*i as i128),
+            _ => None,
+        }
+    }
 }
 
 #[derive(Debug, PartialEq, Clone, Serialize, Deserialize)]
@@ -59,17 +84,77 @@ pub enum ConstrainedType {
     I64,
     /// bool
     Bool,
+    /// float16 (stored as an f32)
+    F16,
+    /// float32
+    F32,
+    /// float64
+    F64,
     /// A tstr of N size
     Str(u64),
     /// A byte array of N size
     Bytes(u64),
+    /// int (unconstrained, only for backends that may own memory)
+    Int,
+    /// uint (unconstrained, only for backends that may own memory)
+    UInt,
+    /// tstr of any size (unconstrained, only for backends that may own memory)
+    TStr,
+    /// bstr of any size (unconstrained, only for backends that may own memory)
+    BStr,
+    /// Any CBOR data item, kept encoded (unconstrained, only for backends that may own memory)
+    Any,
+}
+
+impl ConstrainedType {
+    /// The smallest integer type that can represent every value between min and max
+    pub(crate) fn fit(min: i128, max: i128) -> ConstrainedType {
+        match (min, max) {
+            (0.., ..=0xFF) => ConstrainedType::U8,
+            (0.., ..=0xFFFF) => ConstrainedType::U16,
+            (0.., ..=0xFFFF_FFFF) => ConstrainedType::U32,
+            (0.., _) => ConstrainedType::U64,
+            (-0x80.., ..=0x7F) => ConstrainedType::I8,
+            (-0x8000.., ..=0x7FFF) => ConstrainedType::I16,
+            (-0x8000_0000.., ..=0x7FFF_FFFF) => ConstrainedType::I32,
+            (_, _) => ConstrainedType::I64,
+        }
+    }
+
+    /// The least and the greatest value of an integer type
+    pub(crate) fn bounds(&self) -> Option<(i128, i128)> {
+        match self {
+            ConstrainedType::U8 => Some((0, u8::MAX as i128)),
+            ConstrainedType::U16 => Some((0, u16::MAX as i128)),
+            ConstrainedType::U32 => Some((0, u32::MAX as i128)),
+            ConstrainedType::U64 | ConstrainedType::UInt => Some((0, u64::MAX as i128)),
+            ConstrainedType::I8 => Some((i8::MIN as i128, i8::MAX as i128)),
+            ConstrainedType::I16 => Some((i16::MIN as i128, i16::MAX as i128)),
+            ConstrainedType::I32 => Some((i32::MIN as i128, i32::MAX as i128)),
+            ConstrainedType::I64 | ConstrainedType::Int => {
+                Some((i64::MIN as i128, i64::MAX as i128))
+            }
+            _ => None,
+        }
+    }
 }
 
-#[derive(Debug, PartialEq)]
-pub struct KeyVal(pub(crate) String, pub(crate) Box<Node>);
+/// A named node. When the node is a member of a map, the key on the wire is the name unless the
+/// member declares it's own key, such as an integer or a quoted text key
+#[derive(Debug, Clone, PartialEq)]
+pub struct KeyVal(
+    pub(crate) String,
+    pub(crate) Box<Node>,
+    pub(crate) Option<Literal>,
+);
 impl KeyVal {
     pub fn new<'a, K: Into<Cow<'a, str>>>(key: K, node: Node) -> KeyVal {
-        KeyVal(key.into().into(), Box::new(node))
+        KeyVal(key.into().into(), Box::new(node), None)
+    }
+
+    /// A member of a map written with a key other than it's name
+    pub fn keyed<'a, K: Into<Cow<'a, str>>>(key: K, node: Node, wire: Literal) -> KeyVal {
+        KeyVal(key.into().into(), Box::new(node), Some(wire))
     }
 }
 
@@ -79,18 +164,82 @@ impl From<KeyVal> for Node {
     }
 }
 
-#[derive(Debug, PartialEq)]
+#[derive(Debug, Clone, PartialEq)]
 pub struct Array {
     pub len: usize,
     pub ty: Box<Node>,
 }
 
-#[derive(Debug, PartialEq)]
+#[derive(Debug, Clone, PartialEq)]
+pub struct Vector {
+    pub min: usize,
+    pub max: usize,
+    pub ty: Box<Node>,
+}
+
+/// A map of entries with keys of one type and values of one type, the number of entries is
+/// bounded by max unless max is usize::MAX
+#[derive(Debug, Clone, PartialEq)]
+pub struct Dictionary {
+    pub min: usize,
+    pub max: usize,
+    pub key: Box<Node>,
+    pub val: Box<Node>,
+}
+
+#[derive(Debug, Clone, PartialEq)]
+pub struct Tagged {
+    pub tag: u64,
+    pub ty: Box<Node>,
+}
+
+#[derive(Debug, Clone, PartialEq)]
 pub struct Group {
     pub members: Vec<Node>,
 }
 
-#[derive(Debug, PartialEq)]
+#[derive(Debug, Clone, PartialEq)]
+pub struct Choice {
+    pub variants: Vec<Node>,
+}
+
+#[derive(Debug, Clone, PartialEq)]
+pub struct Range {
+    pub start: Box<Node>,
+    pub end: Box<Node>,
+    pub inclusive: bool,
+}
+
+/// A type constrained by a control operator, other than .size and .tag which are types of their
+/// own. The argument is resolved when linking, because it may reference another rule
+#[derive(Debug, Clone, PartialEq)]
+pub struct Control {
+    pub ty: Box<Node>,
+    pub op: ControlOp,
+    pub arg: Box<Node>,
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum ControlOp {
+    /// The bits of an unsigned integer that may be set, named by a choice of bit positions
+    Bits,
+    Lt,
+    Le,
+    Gt,
+    Ge,
+    Eq,
+    Ne,
+    /// The value of a member that is not given
+    Default,
+    /// A pattern that a text string must match
+    Regexp,
+    /// A byte string that holds the encoding of a value of another type
+    Cbor,
+    /// A byte string that holds the encoding of a sequence of values of another type
+    CborSeq,
+}
+
+#[derive(Debug, Clone, PartialEq)]
 pub enum Node {
     /// A Literal type such as "true" or 3 or "hello"
     Literal(Literal),
@@ -99,15 +248,40 @@ pub enum Node {
     /// A CDDL array defined using square brackets [ ]
     /// IE: [ 3*3 u8 ] ; [u8, u8, u8]
     Array(Array),
+    /// A CDDL array with a variable number of entries up to a bound
+    /// IE: [ 0*8 u8 ] ; up to 8 u8
+    Vector(Vector),
+    /// A CDDL array of entries of different types, where every entry occurs once. The members are
+    /// KeyVals, unnamed entries are named after their position
+    /// IE: point = [ x: i16, y: i16 ] ; or [ i16, i16 ]
+    Record(Group),
     /// A CDDL group defined using braces ( ) and intended used for composing larger types
     /// IE: network-group = (address tstr .size 16, port: uint .size 2)
     Group(Group),
     /// A CDDL map defined using curly braces { }
     /// IE: network = { network-group }
     Map(Group),
+    /// A CDDL map of a single member keyed by a type, with an occurrence
+    /// IE: labels = { 0*8 tstr .size 16 => u32 } ; up to 8 u32 named by text
+    Dictionary(Dictionary),
     /// A single key: value item
     /// IE: foo: int .size 2
     KeyVal(KeyVal),
+    /// A CDDL type choice defined using a slash /
+    /// IE: command = reboot / update
+    Choice(Choice),
+    /// A CDDL range of integers, the bounds may be literals or references to literals
+    /// IE: percent = 0..100
+    Range(Range),
+    /// A group member that may be omitted
+    /// IE: ? name: tstr .size 8
+    Optional(Box<Node>),
+    /// A type behind a CBOR tag
+    /// IE: #6.1(uint .size 4) ; an epoch timestamp
+    Tagged(Tagged),
+    /// A type constrained by a control operator
+    /// IE: uint .le 100 ; or tstr .regexp "[a-z]+"
+    Control(Control),
     /// An unresovoved primative expects to be resolved via second pass when creating a LinkedNode
     /// String is a key to a Node::Foreign (or will error)
     Foreign(String),
@@ -135,18 +309,27 @@ fn flatten_type(ty: &ast::Type) -> FlattenResult<Node> {
     match choices.len() {
         0 => Err(ValidateError::InvalidEnum0),
         1 => Ok(choices.into_iter().next().unwrap()),
-        _ => Err(ValidateError::TodoEnums),
+        _ => Ok(Node::Choice(Choice { variants: choices })),
     }
 }
 
 fn flatten_type1(ty1: &ast::Type1) -> FlattenResult<Node> {
     match ty1 {
         ast::Type1::Simple(ty2) => flatten_type2(ty2),
-        ast::Type1::Range(_) => Err(ValidateError::UnsupportedCddl("range".into())),
+        ast::Type1::Range(range) => flatten_range(range),
         ast::Type1::Control(ctrl) => flatten_control(ctrl),
     }
 }
 
+/// The bounds of a range are resolved when linking, because they may reference another rule
+fn flatten_range(range: &ast::TypeRange) -> FlattenResult<Node> {
+    Ok(Node::Range(Range {
+        start: Box::new(flatten_type2(&range.start)?),
+        end: Box::new(flatten_type2(&range.end)?),
+        inclusive: range.inclusive,
+    }))
+}
+
 fn flatten_type2(ty2: &ast::Type2) -> FlattenResult<Node> {
     use ast::Type2;
     match ty2 {
@@ -156,9 +339,9 @@ fn flatten_type2(ty2: &ast::Type2) -> FlattenResult<Node> {
         Type2::Map(g) => flatten_map(g),
         Type2::Array(g) => flatten_array(g),
         // Type2::Unwrap(r) => Ok(Node::Unwrap(flatten_rule_generic(r)?)),
-        // Type2::ChoiceifyInline(g) => flatten_choiceify_inline(g),
+        Type2::ChoiceifyInline(g) => flatten_choiceify_inline(g),
         // Type2::Choiceify(r) => flatten_choiceify(r),
-        _ => unimplemented!(),
+        _ => Err(ValidateError::UnsupportedCddl(format!("{:?}", ty2))),
     }
 }
 
@@ -166,7 +349,7 @@ fn flatten_type2(ty2: &ast::Type2) -> FlattenResult<Node> {
 fn flatten_value(val: &ast::Value) -> FlattenResult<Node> {
     use ast::Value;
     match val {
-        Value::Text(s) => Ok(Node::Literal(flatten_literal_text(s)?)),
+        Value::Text(s) => Ok(Node::Literal(Literal::Str(s.clone()))),
         Value::Nint(i) => Ok(Node::Literal(Literal::Int(*i))),
         Value::Uint(i) => Ok(Node::Literal(Literal::UInt(*i))),
         Value::Bytes(b) => Ok(Node::Literal(Literal::Bytes(b.clone()))),
@@ -174,23 +357,19 @@ fn flatten_value(val: &ast::Value) -> FlattenResult<Node> {
     }
 }
 
-fn flatten_literal_text(val: &String) -> FlattenResult<Literal> {
-    use ValidateError::*;
-    if val.len() == 1 {
-        Ok(Literal::Char(val.chars().next().ok_or(Infallible)?))
-    } else {
-        Ok(Literal::Str(val.to_string()))
-    }
-}
-
-/// If we flatten a type2 typename we must do so via a control statement. Otherwize we assume we
-/// are an unresolved named type
+/// If we flatten a type2 typename without a control statement the primative is unconstrained, and
+/// the backend decides if it can be represented. Otherwize we assume we are an unresolved named
+/// type
 fn flatten_typename(name: &ast::NameGeneric) -> FlattenResult<Node> {
     match flatten_primative(&name.name) {
-        PrimativeType::Int | PrimativeType::UInt | PrimativeType::TStr | PrimativeType::BStr => {
-            Err(ValidateError::InvalidUnconstrainedPrimative)
-        }
+        PrimativeType::Int => Ok(Node::ConstrainedType(ConstrainedType::Int)),
+        PrimativeType::UInt => Ok(Node::ConstrainedType(ConstrainedType::UInt)),
+        PrimativeType::TStr => Ok(Node::ConstrainedType(ConstrainedType::TStr)),
+        PrimativeType::BStr => Ok(Node::ConstrainedType(ConstrainedType::BStr)),
         PrimativeType::Bool => Ok(Node::ConstrainedType(ConstrainedType::Bool)),
+        PrimativeType::Float16 => Ok(Node::ConstrainedType(ConstrainedType::F16)),
+        PrimativeType::Float32 => Ok(Node::ConstrainedType(ConstrainedType::F32)),
+        PrimativeType::Float64 => Ok(Node::ConstrainedType(ConstrainedType::F64)),
         PrimativeType::Unresolved(s) => Ok(Node::Foreign(s)),
     }
 }
@@ -205,6 +384,9 @@ fn flatten_primative(prim: &str) -> PrimativeType {
         "tstr" | "text" => PrimativeType::TStr,
         "bstr" | "bytes" => PrimativeType::BStr,
         "bool" | "boolean" => PrimativeType::Bool,
+        "float16" => PrimativeType::Float16,
+        "float32" | "float16-32" => PrimativeType::Float32,
+        "float64" | "float32-64" | "float" => PrimativeType::Float64,
         s => PrimativeType::Unresolved(s.into()),
     }
 }
@@ -212,10 +394,30 @@ fn flatten_primative(prim: &str) -> PrimativeType {
 fn flatten_control(ctl: &ast::TypeControl) -> FlattenResult<Node> {
     match ctl.op.as_str() {
         "size" => flatten_control_size(ctl),
+        "tag" => flatten_control_tag(ctl),
+        "bits" => flatten_control_op(ctl, ControlOp::Bits),
+        "lt" => flatten_control_op(ctl, ControlOp::Lt),
+        "le" => flatten_control_op(ctl, ControlOp::Le),
+        "gt" => flatten_control_op(ctl, ControlOp::Gt),
+        "ge" => flatten_control_op(ctl, ControlOp::Ge),
+        "eq" => flatten_control_op(ctl, ControlOp::Eq),
+        "ne" => flatten_control_op(ctl, ControlOp::Ne),
+        "default" => flatten_control_op(ctl, ControlOp::Default),
+        "regexp" => flatten_control_op(ctl, ControlOp::Regexp),
+        "cbor" => flatten_control_op(ctl, ControlOp::Cbor),
+        "cborseq" => flatten_control_op(ctl, ControlOp::CborSeq),
         ctrl => Err(ValidateError::UnsupportedCddl(ctrl.to_string())),
     }
 }
 
+fn flatten_control_op(ctrl: &ast::TypeControl, op: ControlOp) -> FlattenResult<Node> {
+    Ok(Node::Control(Control {
+        ty: Box::new(flatten_type2(&ctrl.target)?),
+        op,
+        arg: Box::new(flatten_type2(&ctrl.arg)?),
+    }))
+}
+
 /// Take a control type, and turn it into a constrained type
 fn control_to_constrained_type(ctrl: &ast::TypeControl) -> FlattenResult<ConstrainedType> {
     use ast::{Type2, Value};
@@ -242,38 +444,209 @@ fn flatten_control_size(ctrl: &ast::TypeControl) -> FlattenResult<Node> {
     control_to_constrained_type(ctrl).map(Node::ConstrainedType)
 }
 
+/// A tagged type is written #6.n(type), which we rewrite to the control (type) .tag n before
+/// parsing (see util::desugar_tags)
+fn flatten_control_tag(ctrl: &ast::TypeControl) -> FlattenResult<Node> {
+    match &ctrl.arg {
+        ast::Type2::Value(ast::Value::Uint(tag)) => Ok(Node::Tagged(Tagged {
+            tag: *tag,
+            ty: Box::new(flatten_type2(&ctrl.target)?),
+        })),
+        _ => Err(ValidateError::InvalidControlArg),
+    }
+}
+
+/// A choice made from the members of a group is a choice of their values, named by their keys
+/// IE: &( read: 0, write: 1 )
+fn flatten_choiceify_inline(group: &ast::Group) -> FlattenResult<Node> {
+    get_group_entries(group)?
+        .iter()
+        .map(|entry| match flatten_groupentry(entry)? {
+            node @ Node::KeyVal(_) if entry.occur.is_none() => Ok(node),
+            _ => Err(ValidateError::InvalidChoice),
+        })
+        .collect::<FlattenResult<Vec<Node>>>()
+        .map(|variants| Node::Choice(Choice { variants }))
+}
+
+/// A map of a single member keyed by a type (IE: * tstr => u32) is a dictionary of that member,
+/// any other map is a struct of it's members
 fn flatten_map(group: &ast::Group) -> FlattenResult<Node> {
-    flatten_group(group).map(|members| Node::Map(Group { members }))
+    match group.0.as_slice() {
+        [ast::GrpChoice(entries)] => match entries.as_slice() {
+            [entry] if is_wildcard(entry) => flatten_dictionary(entry),
+            _ => flatten_group(group).map(|members| Node::Map(Group { members })),
+        },
+        _ => flatten_group(group).map(|members| Node::Map(Group { members })),
+    }
+}
+
+fn is_wildcard(group_entry: &ast::GrpEnt) -> bool {
+    use ast::{MemberKeyVal, Type1, Type2};
+    match &group_entry.val {
+        ast::GrpEntVal::Member(ast::Member { key: Some(key), .. }) => match &key.val {
+            MemberKeyVal::Type1(Type1::Simple(Type2::Value(_))) => false,
+            MemberKeyVal::Type1(_) => true,
+            _ => false,
+        },
+        _ => false,
+    }
 }
 
+/// The occurrence of the member of a dictionary bounds it's number of entries
+fn flatten_dictionary(group_entry: &ast::GrpEnt) -> FlattenResult<Node> {
+    use ast::{MemberKeyVal, Occur};
+    let (min, max) = match group_entry.occur {
+        None => (1, 1),
+        Some(Occur::Optional) => (0, 1),
+        Some(Occur::ZeroOrMore) => (0, usize::MAX),
+        Some(Occur::OneOrMore) => (1, usize::MAX),
+        Some(Occur::Numbered(min, max)) if min <= max && max > 0 => (min, max),
+        Some(Occur::Numbered(..)) => return Err(ValidateError::InvalidArraySize),
+    };
+    match &group_entry.val {
+        ast::GrpEntVal::Member(ast::Member {
+            key:
+                Some(ast::MemberKey {
+                    val: MemberKeyVal::Type1(key),
+                    ..
+                }),
+            value,
+        }) => Ok(Node::Dictionary(Dictionary {
+            min,
+            max,
+            key: Box::new(flatten_type1(key)?),
+            val: Box::new(flatten_type(value)?),
+        })),
+        _ => Err(ValidateError::InvalidGroupMissingKey),
+    }
+}
+
+/// An array of a single entry with an occurrence is an array or a vector of that entry, any other
+/// array is a record of it's entries
 fn flatten_array(group: &ast::Group) -> FlattenResult<Node> {
     use ast::Occur;
-    get_group_entries(group).and_then(|entries| {
-        if entries.len() == 1 {
-            let ty = Box::new(flatten_groupentry(&entries[0])?);
-            match entries[0].occur {
+    get_group_entries(group).and_then(|entries| match entries.as_slice() {
+        [entry] if entry.occur.is_some() => {
+            let ty = Box::new(flatten_array_entry(entry)?);
+            match entry.occur {
                 Some(Occur::Numbered(a, len)) if a == len => Ok(Node::Array(Array { len, ty })),
+                Some(Occur::Numbered(min, max)) if min < max => {
+                    Ok(Node::Vector(Vector { min, max, ty }))
+                }
+                Some(Occur::Optional) => Ok(Node::Vector(Vector { min: 0, max: 1, ty })),
                 _ => Err(ValidateError::InvalidArraySize),
             }
-        } else {
-            Err(ValidateError::InvalidArray)
         }
+        entries => flatten_record(entries),
     })
 }
 
+/// The entries of a record are either all named, or all unnamed. Unnamed entries are named after
+/// their position so that they can be rendered as a tuple, literals are not stored and are not
+/// counted
+fn flatten_record(entries: &[ast::GrpEnt]) -> FlattenResult<Node> {
+    let named = entries.iter().filter(|entry| is_named(entry)).count();
+    if named != 0 && named != entries.len() {
+        return Err(ValidateError::UnsupportedCddl(
+            "record of named and unnamed entries".into(),
+        ));
+    }
+    if named != 0 {
+        let members = entries
+            .iter()
+            .map(flatten_groupentry_occur)
+            .collect::<FlattenResult<Vec<Node>>>()?;
+        return Ok(Node::Record(Group { members }));
+    }
+    let mut position = 0;
+    let members = entries
+        .iter()
+        .enumerate()
+        .map(|(n, entry)| match flatten_record_entry(entry)? {
+            node @ Node::Literal(_) => Ok(KeyVal::new(format!("literal-{}", n), node).into()),
+            node => {
+                position += 1;
+                Ok(KeyVal::new((position - 1).to_string(), node).into())
+            }
+        })
+        .collect::<FlattenResult<Vec<Node>>>()?;
+    Ok(Node::Record(Group { members }))
+}
+
+/// An unnamed entry of a record is a type, which may be optional
+fn flatten_record_entry(group_entry: &ast::GrpEnt) -> FlattenResult<Node> {
+    use ast::Occur;
+    let node = flatten_array_entry(group_entry)?;
+    match group_entry.occur {
+        None => Ok(node),
+        Some(Occur::Optional) | Some(Occur::Numbered(0, 1)) => Ok(Node::Optional(Box::new(node))),
+        Some(_) => Err(ValidateError::UnsupportedCddl("occurrence".into())),
+    }
+}
+
+fn is_named(group_entry: &ast::GrpEnt) -> bool {
+    matches!(
+        &group_entry.val,
+        ast::GrpEntVal::Member(ast::Member { key: Some(_), .. })
+    )
+}
+
+/// The entry of an array is a type, unlike members of a group which may only be unnamed when
+/// they refer to another group
+fn flatten_array_entry(group_entry: &ast::GrpEnt) -> FlattenResult<Node> {
+    match &group_entry.val {
+        ast::GrpEntVal::Member(ast::Member { key: None, value }) => flatten_type(value),
+        _ => flatten_groupentry(group_entry),
+    }
+}
+
+/// A group with more than one GrpChoice is flattened into a single Choice member, where each
+/// variant is a Group of the members of that choice
 fn flatten_group(group: &ast::Group) -> FlattenResult<Vec<Node>> {
-    get_group_entries(group)?
-        .into_iter()
-        .map(flatten_groupentry)
-        .collect()
+    match group.0.as_slice() {
+        [choice] => flatten_grpchoice(choice),
+        choices => choices
+            .iter()
+            .map(|choice| flatten_grpchoice(choice).map(|members| Node::Group(Group { members })))
+            .collect::<FlattenResult<Vec<Node>>>()
+            .map(|variants| vec![Node::Choice(Choice { variants })]),
+    }
 }
 
-// We don't support "choices" or "enums", therefore we assume GrpChoice==1
+fn flatten_grpchoice(choice: &ast::GrpChoice) -> FlattenResult<Vec<Node>> {
+    choice.0.iter().map(flatten_groupentry_occur).collect()
+}
+
+/// The occurrence of a group member is only supported to declare the member optional. (Arrays
+/// read the occurrence of their entry separately)
+fn flatten_groupentry_occur(group_entry: &ast::GrpEnt) -> FlattenResult<Node> {
+    use ast::Occur;
+    match (&group_entry.occur, flatten_groupentry(group_entry)?) {
+        (None, node) => Ok(node),
+        (
+            Some(Occur::Optional) | Some(Occur::Numbered(0, 1)),
+            Node::KeyVal(KeyVal(key, val, wire)),
+        ) => Ok(Node::KeyVal(KeyVal(
+            key,
+            Box::new(Node::Optional(val)),
+            wire,
+        ))),
+        (Some(Occur::Optional) | Some(Occur::Numbered(0, 1)), _) => {
+            Err(ValidateError::UnsupportedCddl("optional group".into()))
+        }
+        (Some(_), _) => Err(ValidateError::UnsupportedCddl("occurrence".into())),
+    }
+}
+
+// We don't support group choices inside of arrays, therefore we assume GrpChoice==1
 fn get_group_entries(group: &ast::Group) -> FlattenResult<&Vec<ast::GrpEnt>> {
     if group.0.len() == 1 {
         Ok(&group.0[0].0)
     } else {
-        Err(ValidateError::InvalidEnum0)
+        Err(ValidateError::UnsupportedCddl(
+            "group choice in array".into(),
+        ))
     }
 }
 
@@ -295,12 +668,41 @@ fn flatten_group_member(member: &ast::Member) -> FlattenResult<Node> {
             MemberKeyVal::Bareword(s) => {
                 Ok(Node::KeyVal(KeyVal::new(s, flatten_type(&member.value)?)))
             }
+            MemberKeyVal::Value(value)
+            | MemberKeyVal::Type1(ast::Type1::Simple(ast::Type2::Value(value))) => {
+                let (name, wire) = member_key(value)?;
+                let node = flatten_type(&member.value)?;
+                Ok(Node::KeyVal(KeyVal::keyed(name, node, wire)))
+            }
             _ => Err(ValidateError::InvalidGroupMissingKey),
         },
         None => assume_foreign_value(&member.value),
     }
 }
 
+/// The name and the wire key of a member with an integer or a quoted text key. An integer key is
+/// named after it's value, and a text key is sanitised into a name we can render as a field
+fn member_key(value: &ast::Value) -> FlattenResult<(String, Literal)> {
+    match value {
+        ast::Value::Uint(n) => Ok((format!("key-{}", n), Literal::UInt(*n))),
+        ast::Value::Nint(n) => Ok((format!("key-neg-{}", n.unsigned_abs()), Literal::Int(*n))),
+        ast::Value::Text(text) => {
+            let name = text
+                .split(|c: char| !c.is_ascii_alphanumeric())
+                .filter(|word| !word.is_empty())
+                .collect::<Vec<&str>>()
+                .join("-");
+            let name = match name.chars().next() {
+                Some(c) if c.is_ascii_alphabetic() => name,
+                Some(_) => format!("key-{}", name),
+                None => "key".into(),
+            };
+            Ok((name, Literal::Str(text.clone())))
+        }
+        _ => Err(ValidateError::UnsupportedCddl("map key".into())),
+    }
+}
+
 fn assume_foreign_value(ty: &ast::Type) -> FlattenResult<Node> {
     match flatten_type(ty) {
         Ok(Node::Foreign(s)) => Ok(Node::Foreign(s)),
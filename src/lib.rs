@@ -1,5 +1,6 @@
 mod gen;
 mod ivt;
+mod prelude;
 mod util;
 mod validate;
 
@@ -9,21 +10,37 @@ mod tests;
 use cddl_cat::ast;
 use gen::gen_lib;
 use ivt::flatten_rule;
+use prelude::prelude;
 use std::collections::BTreeMap;
-use validate::link_node;
+use util::desugar_tags;
+use validate::{hoist, link_rule, Context};
 
-pub use gen::{Language, Options, RenderError, RenderResult};
-pub use ivt::{Array, ConstrainedType, FlattenResult, Group, KeyVal, Literal, Node};
+pub use gen::{Alias, Language, Options, RenderError, RenderResult};
+pub use ivt::{
+    Array, Choice, ConstrainedType, Control, ControlOp, Dictionary, FlattenResult, Group, KeyVal,
+    Literal, Node, Range, Tagged, Vector,
+};
 pub use util::ValidateError;
-pub use validate::{Fields, LinkedArray, LinkedKeyVal, LinkedNode};
+pub use validate::{
+    Fields, LinkedAlias, LinkedArray, LinkedBits, LinkedChoice, LinkedDefault, LinkedDictionary,
+    LinkedEmbedded, LinkedKeyVal, LinkedNode, LinkedRange, LinkedRegexp, LinkedTagged, LinkedUnion,
+    LinkedVector,
+};
 
 /// Take a string of CDDL text, and create a Flattened representation of
 /// data types useful for further processing and generating code with.
+/// Types of the standard prelude are resolved for the default language.
 pub fn parse(cddl: &str) -> FlattenResult<BTreeMap<String, LinkedNode>> {
-    cddl_cat::parse_cddl(cddl)
+    parse_for(cddl, &Language::default())
+}
+
+/// Similar to parse, but types of the standard prelude (tdate, uri, etc) are resolved
+/// to the types we render for a language.
+pub fn parse_for(cddl: &str, language: &Language) -> FlattenResult<BTreeMap<String, LinkedNode>> {
+    cddl_cat::parse_cddl(&desugar_tags(cddl))
         .map_err(ValidateError::from)
         .and_then(|nodes| flatten(&nodes))
-        .and_then(|nodes| link(&nodes))
+        .and_then(|nodes| link_for(&nodes, language))
 }
 
 /// Take already parsed CDDL and generate a Representation that is useful
@@ -41,16 +58,29 @@ pub fn flatten(cddl: &ast::Cddl) -> FlattenResult<BTreeMap<String, Node>> {
 /// NOTE prefer parse wrapper. Access to an unlinked tree not particularly useful
 /// outside of this module, but is temporarily here for development.
 pub fn link(nodes: &BTreeMap<String, Node>) -> FlattenResult<BTreeMap<String, LinkedNode>> {
+    link_for(nodes, &Language::default())
+}
+
+/// Similar to link, but references to the standard prelude are resolved for a language.
+/// Only the nodes passed in are returned, the prelude is not rendered.
+pub fn link_for(
+    nodes: &BTreeMap<String, Node>,
+    language: &Language,
+) -> FlattenResult<BTreeMap<String, LinkedNode>> {
+    let mut rules = prelude(language)?;
+    rules.extend(nodes.iter().map(|(key, node)| (key.clone(), node.clone())));
+    let ctx = Context::new(&rules, nodes);
     nodes
         .iter()
-        .map(|(key, node)| link_node(node, nodes).map(|node| (key.clone(), node)))
-        .collect()
+        .map(|(key, node)| link_rule(key, node, &ctx).map(|node| (key.clone(), node)))
+        .collect::<FlattenResult<BTreeMap<String, LinkedNode>>>()
+        .and_then(hoist)
 }
 
 /// We have some CDDL and we want to generate lib.rs
 /// TODO create a cursor and return length from our bindings...
 pub fn render_lib(s: &str, mode: &Options) -> RenderResult<String> {
-    parse(s)
+    parse_for(s, &mode.language)
         .map_err(RenderError::from)
         .and_then(|nodes| gen_lib(nodes, mode))
 }
@@ -0,0 +1,52 @@
+use crate::flatten;
+use crate::gen::Language;
+use crate::ivt::*;
+use crate::util::{desugar_tags, ValidateError};
+use std::collections::BTreeMap;
+
+/// Types of the standard prelude (RFC 8610 Appendix D) that are rendered the same way for every
+/// language. NOTE a time may also be a float, but we only store whole seconds
+const PRELUDE: &str = r#"
+time = #6.1(int .size 8)
+biguint = #6.2(bstr)
+bignint = #6.3(bstr)
+"#;
+
+/// Text types of the prelude, stored in a fixed string when we cannot own memory
+const PRELUDE_C: &str = r#"
+tdate = #6.0(tstr .size 36)
+uri = #6.32(tstr .size 256)
+b64url = #6.33(tstr .size 256)
+b64legacy = #6.34(tstr .size 256)
+regexp = #6.35(tstr .size 256)
+mime-message = #6.36(tstr .size 256)
+"#;
+
+/// Text types of the prelude, stored in a String when we may own memory
+const PRELUDE_OWNED: &str = r#"
+tdate = #6.0(tstr)
+uri = #6.32(tstr)
+b64url = #6.33(tstr)
+b64legacy = #6.34(tstr)
+regexp = #6.35(tstr)
+mime-message = #6.36(tstr)
+"#;
+
+/// The rules of the standard prelude for a language. A rule of the CDDL with the same name
+/// takes precedence over the prelude
+pub(crate) fn prelude(language: &Language) -> FlattenResult<BTreeMap<String, Node>> {
+    let text = match language {
+        Language::C => PRELUDE_C,
+        Language::Rust | Language::Typescript => PRELUDE_OWNED,
+    };
+    let mut nodes = cddl_cat::parse_cddl(&desugar_tags(&format!("{}{}", PRELUDE, text)))
+        .map_err(ValidateError::from)
+        .and_then(|cddl| flatten(&cddl))?;
+    // Some of the prelude is not expressible by our parser, so we add it by hand
+    nodes.insert("null".into(), Node::Literal(Literal::Null));
+    nodes.insert("nil".into(), Node::Literal(Literal::Null));
+    nodes.insert("true".into(), Node::Literal(Literal::Bool(true)));
+    nodes.insert("false".into(), Node::Literal(Literal::Bool(false)));
+    nodes.insert("any".into(), Node::ConstrainedType(ConstrainedType::Any));
+    Ok(nodes)
+}
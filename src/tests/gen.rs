@@ -0,0 +1,30 @@
+use crate::gen::gen_lib;
+use crate::*;
+use std::collections::BTreeMap;
+
+#[test]
+fn render_literal_text() {
+    // Control characters may not be written by CDDL, but a literal may carry them all the same
+    let text = "bell\x07 esc\x1b2 café \"quoted\"\n";
+    let nodes = BTreeMap::from([(
+        "greeting".to_string(),
+        LinkedNode::Literal(Literal::Str(text.into())),
+    )]);
+    let render = |language| {
+        let opts = Options {
+            language,
+            ..Options::default()
+        };
+        gen_lib(nodes.clone(), &opts).unwrap()
+    };
+
+    // cbindgen copies the literal into a #define, so it is escaped the way C and rust agree on. A
+    // hex digit after a hex escape is escaped too, and text that is not ASCII is written as UTF-8
+    let c = render(Language::C);
+    assert!(c.contains(r#"pub const GREETING: &str = "bell\x07 esc\x1b\x32 café \"quoted\"\n";"#));
+    assert!(!c.contains(r"\u{"));
+
+    // Otherwise the literal is escaped like any rust string
+    let rust = render(Language::Rust);
+    assert!(rust.contains(&format!("pub const GREETING: &str = {:?};", text)));
+}
@@ -34,8 +34,8 @@ fn parse() {
         linked["colors"],
         LinkedNode::Struct(Fields {
             members: vec![
-                LinkedKeyVal::new("car", ConstrainedType::U8.into()).into(),
-                LinkedKeyVal::new("boat", ConstrainedType::U8.into()).into(),
+                LinkedKeyVal::new("car", alias("u8", ConstrainedType::U8.into())),
+                LinkedKeyVal::new("boat", alias("u8", ConstrainedType::U8.into())),
             ]
         })
     );
@@ -57,9 +57,9 @@ fn parse() {
         linked["ip"],
         LinkedNode::Fields(Fields {
             members: vec![
-                LinkedKeyVal::new("address", ConstrainedType::Str(16).into()).into(),
-                LinkedKeyVal::new("port", ConstrainedType::U16.into()).into(),
-                LinkedKeyVal::new("dhcp", ConstrainedType::Bool.into()).into(),
+                LinkedKeyVal::new("address", ConstrainedType::Str(16).into()),
+                LinkedKeyVal::new("port", alias("u16", ConstrainedType::U16.into())),
+                LinkedKeyVal::new("dhcp", alias("is-dhcp", ConstrainedType::Bool.into())),
             ]
         })
     );
@@ -77,9 +77,9 @@ fn parse() {
         linked["local"],
         LinkedNode::Struct(Fields {
             members: vec![
-                LinkedKeyVal::new("address", ConstrainedType::Str(16).into()).into(),
-                LinkedKeyVal::new("port", ConstrainedType::U16.into()).into(),
-                LinkedKeyVal::new("dhcp", ConstrainedType::Bool.into()).into(),
+                LinkedKeyVal::new("address", ConstrainedType::Str(16).into()),
+                LinkedKeyVal::new("port", alias("u16", ConstrainedType::U16.into())),
+                LinkedKeyVal::new("dhcp", alias("is-dhcp", ConstrainedType::Bool.into())),
             ]
         })
     );
@@ -112,11 +112,11 @@ fn parse() {
         })
     );
 
-    // A flattened array with linked member
+    // A flattened array with linked member, which is an alias of an alias
     assert_eq!(
         linked["mac"],
         LinkedNode::Array(LinkedArray {
-            ty: Box::new(LinkedNode::ConstrainedType(ConstrainedType::U8)),
+            ty: Box::new(alias("small", alias("u8", ConstrainedType::U8.into()))),
             len: 6
         })
     );
@@ -136,3 +136,1224 @@ fn parse() {
         LinkedNode::Literal(Literal::Str("bar".into()))
     );
 }
+
+#[test]
+fn parse_choice() {
+    let cddl = parse_cddl(&read_cddl("test.cddl")).unwrap();
+    let node = flatten(&cddl).unwrap();
+    let linked = link(&node).unwrap();
+
+    // A flattened choice
+    assert_eq!(
+        node["command"],
+        Node::Choice(Choice {
+            variants: vec![
+                Node::Foreign("colors".into()),
+                Node::Foreign("local-nested".into()),
+                Node::Foreign("mac".into()),
+            ]
+        })
+    );
+
+    // A flattened linked choice with variants named after their rules
+    assert_eq!(
+        linked["command"],
+        LinkedNode::Choice(LinkedChoice {
+            variants: vec![
+                LinkedKeyVal::new("colors", LinkedNode::ForeignStruct("colors".into())),
+                LinkedKeyVal::new(
+                    "local-nested",
+                    LinkedNode::ForeignStruct("local-nested".into())
+                ),
+                LinkedKeyVal::new(
+                    "mac",
+                    alias(
+                        "mac",
+                        LinkedNode::Array(LinkedArray {
+                            ty: Box::new(alias("small", alias("u8", ConstrainedType::U8.into()))),
+                            len: 6
+                        })
+                    )
+                ),
+            ]
+        })
+    );
+
+    // A choice with anonymous variants
+    let node = flatten(&parse_cddl("a = uint .size 1 / bool").unwrap()).unwrap();
+    assert_eq!(
+        link(&node).unwrap()["a"],
+        LinkedNode::Choice(LinkedChoice {
+            variants: vec![
+                LinkedKeyVal::new("variant-0", ConstrainedType::U8.into()),
+                LinkedKeyVal::new("variant-1", ConstrainedType::Bool.into()),
+            ]
+        })
+    );
+
+    // A choice must have unique variants
+    let node = flatten(&parse_cddl("a = b / b\nb = bool").unwrap()).unwrap();
+    assert_eq!(link(&node), Err(ValidateError::InvalidChoice));
+}
+
+#[test]
+fn parse_enum() {
+    let cddl = parse_cddl(&read_cddl("test.cddl")).unwrap();
+    let node = flatten(&cddl).unwrap();
+    let linked = link(&node).unwrap();
+
+    // A flattened choice of literals
+    assert_eq!(
+        node["color"],
+        Node::Choice(Choice {
+            variants: vec![
+                Node::Literal(Literal::Str("red".into())),
+                Node::Literal(Literal::Str("green".into())),
+            ]
+        })
+    );
+
+    // A linked choice of literals named by their rule, or their value
+    assert_eq!(
+        linked["mode"],
+        LinkedNode::Choice(LinkedChoice {
+            variants: vec![
+                LinkedKeyVal::new("off", LinkedNode::Literal(Literal::UInt(0))),
+                LinkedKeyVal::new("on", LinkedNode::Literal(Literal::UInt(1))),
+                LinkedKeyVal::new("value-2", LinkedNode::Literal(Literal::UInt(2))),
+            ]
+        })
+    );
+
+    // A linked choice of text literals
+    assert_eq!(
+        linked["color"],
+        LinkedNode::Choice(LinkedChoice {
+            variants: vec![
+                LinkedKeyVal::new("red", LinkedNode::Literal(Literal::Str("red".into()))),
+                LinkedKeyVal::new("green", LinkedNode::Literal(Literal::Str("green".into()))),
+            ]
+        })
+    );
+
+    // Literals can not be mixed with other kinds of literals or types
+    let node = flatten(&parse_cddl(r#"a = 1 / "one""#).unwrap()).unwrap();
+    assert_eq!(link(&node), Err(ValidateError::InvalidChoice));
+    let node = flatten(&parse_cddl("a = 1 / b\nb = bool").unwrap()).unwrap();
+    assert_eq!(link(&node), Err(ValidateError::InvalidChoice));
+
+    // Enum values must be unique
+    let node = flatten(&parse_cddl("a = 1 / b\nb = 1").unwrap()).unwrap();
+    assert_eq!(link(&node), Err(ValidateError::InvalidChoice));
+
+    // Enum variants must remain unique once they are cased
+    let node = flatten(&parse_cddl(r#"color = "a b" / "a-b""#).unwrap()).unwrap();
+    assert_eq!(link(&node), Err(ValidateError::InvalidChoice));
+}
+
+#[test]
+fn parse_union() {
+    let cddl = parse_cddl(&read_cddl("test.cddl")).unwrap();
+    let node = flatten(&cddl).unwrap();
+    let linked = link(&node).unwrap();
+
+    // A flattened group choice
+    assert_eq!(
+        node["msg"],
+        Node::Map(Group {
+            members: vec![Node::Choice(Choice {
+                variants: vec![
+                    Node::Group(Group {
+                        members: vec![
+                            Node::KeyVal(KeyVal::new("type", Node::Literal(Literal::UInt(1)))),
+                            Node::KeyVal(KeyVal::new("a", Node::Foreign("u8".into()))),
+                        ]
+                    }),
+                    Node::Group(Group {
+                        members: vec![
+                            Node::KeyVal(KeyVal::new("type", Node::Literal(Literal::UInt(2)))),
+                            Node::KeyVal(KeyVal::new("b", Node::Foreign("u16".into()))),
+                        ]
+                    }),
+                ]
+            })]
+        })
+    );
+
+    // A linked group choice with variants named after their discriminant
+    assert_eq!(
+        linked["msg"],
+        LinkedNode::Union(LinkedUnion {
+            discriminant: "type".into(),
+            variants: vec![
+                LinkedKeyVal::new(
+                    "type-1",
+                    LinkedNode::Fields(Fields {
+                        members: vec![
+                            LinkedKeyVal::new("type", LinkedNode::Literal(Literal::UInt(1))),
+                            LinkedKeyVal::new("a", alias("u8", ConstrainedType::U8.into())),
+                        ]
+                    })
+                ),
+                LinkedKeyVal::new(
+                    "type-2",
+                    LinkedNode::Fields(Fields {
+                        members: vec![
+                            LinkedKeyVal::new("type", LinkedNode::Literal(Literal::UInt(2))),
+                            LinkedKeyVal::new("b", alias("u16", ConstrainedType::U16.into())),
+                        ]
+                    })
+                ),
+            ]
+        })
+    );
+
+    // A group choice must have a discriminant with a unique value in every variant
+    let node = flatten(&parse_cddl("a = { t: 1, b: bool // t: 1, c: bool }").unwrap()).unwrap();
+    assert_eq!(link(&node), Err(ValidateError::InvalidChoice));
+    let node = flatten(&parse_cddl("a = { t: 1, b: bool // c: bool }").unwrap()).unwrap();
+    assert_eq!(link(&node), Err(ValidateError::InvalidChoice));
+
+    // Union variants must remain unique once they are cased
+    let cddl = r#"msg = { kind: "a b", b: bool // kind: "a-b", c: bool }"#;
+    let node = flatten(&parse_cddl(cddl).unwrap()).unwrap();
+    assert_eq!(link(&node), Err(ValidateError::InvalidChoice));
+}
+
+#[test]
+fn parse_range() {
+    let cddl = parse_cddl(&read_cddl("test.cddl")).unwrap();
+    let node = flatten(&cddl).unwrap();
+    let linked = link(&node).unwrap();
+
+    // A flattened range keeps it's bounds unresolved
+    assert_eq!(
+        node["level"],
+        Node::Range(Range {
+            start: Box::new(Node::Literal(Literal::UInt(1))),
+            end: Box::new(Node::Foreign("lit".into())),
+            inclusive: false,
+        })
+    );
+
+    // A linked range is inclusive and uses the smallest type that fits
+    assert_eq!(
+        linked["percent"],
+        LinkedNode::Range(LinkedRange {
+            ty: ConstrainedType::U8,
+            min: Literal::UInt(0),
+            max: Literal::UInt(100),
+            ne: None,
+        })
+    );
+    assert_eq!(
+        linked["level"],
+        LinkedNode::Range(LinkedRange {
+            ty: ConstrainedType::U8,
+            min: Literal::UInt(1),
+            max: Literal::UInt(2),
+            ne: None,
+        })
+    );
+    assert_eq!(
+        linked["celsius"],
+        LinkedNode::Range(LinkedRange {
+            ty: ConstrainedType::I16,
+            min: Literal::Int(-129),
+            max: Literal::UInt(0),
+            ne: None,
+        })
+    );
+
+    // A range must have integer bounds with min <= max
+    let node = flatten(&parse_cddl("a = 3..1").unwrap()).unwrap();
+    assert_eq!(link(&node), Err(ValidateError::InvalidRange));
+    let node = flatten(&parse_cddl("a = 1..b\nb = \"b\"").unwrap()).unwrap();
+    assert_eq!(link(&node), Err(ValidateError::InvalidRange));
+}
+
+#[test]
+fn parse_optional() {
+    let cddl = parse_cddl(&read_cddl("test.cddl")).unwrap();
+    let node = flatten(&cddl).unwrap();
+    let linked = link(&node).unwrap();
+
+    // A flattened struct with an optional member
+    assert_eq!(
+        node["device"],
+        Node::Map(Group {
+            members: vec![
+                KeyVal::new("id", Node::Foreign("u8".into())).into(),
+                KeyVal::new(
+                    "name",
+                    Node::Optional(Box::new(ConstrainedType::Str(8).into()))
+                )
+                .into(),
+            ]
+        })
+    );
+
+    // A linked struct with an optional member
+    assert_eq!(
+        linked["device"],
+        LinkedNode::Struct(Fields {
+            members: vec![
+                LinkedKeyVal::new("id", alias("u8", ConstrainedType::U8.into())),
+                LinkedKeyVal::new(
+                    "name",
+                    LinkedNode::Optional(Box::new(ConstrainedType::Str(8).into()))
+                ),
+            ]
+        })
+    );
+
+    // An optional member must carry a value
+    let node = flatten(&parse_cddl("a = { ? b: 1 }").unwrap()).unwrap();
+    assert_eq!(link(&node), Err(ValidateError::InvalidLiteral));
+
+    // Only keyed members may be optional
+    let cddl = parse_cddl("a = { ? b }\nb = ( c: uint )").unwrap();
+    assert_eq!(
+        flatten(&cddl),
+        Err(ValidateError::UnsupportedCddl("optional group".into()))
+    );
+}
+
+#[test]
+fn parse_vector() {
+    let cddl = parse_cddl(&read_cddl("test.cddl")).unwrap();
+    let node = flatten(&cddl).unwrap();
+    let linked = link(&node).unwrap();
+
+    // A flattened struct with vector members
+    assert_eq!(
+        node["history"],
+        Node::Map(Group {
+            members: vec![
+                KeyVal::new(
+                    "readings",
+                    Node::Vector(Vector {
+                        min: 0,
+                        max: 8,
+                        ty: Box::new(Node::Foreign("u16".into()))
+                    })
+                )
+                .into(),
+                KeyVal::new(
+                    "peers",
+                    Node::Vector(Vector {
+                        min: 1,
+                        max: 2,
+                        ty: Box::new(Node::Foreign("colors".into()))
+                    })
+                )
+                .into(),
+            ]
+        })
+    );
+
+    // A linked struct with vector members
+    assert_eq!(
+        linked["history"],
+        LinkedNode::Struct(Fields {
+            members: vec![
+                LinkedKeyVal::new(
+                    "readings",
+                    LinkedNode::Vector(LinkedVector {
+                        min: 0,
+                        max: 8,
+                        ty: Box::new(alias("u16", ConstrainedType::U16.into()))
+                    })
+                ),
+                LinkedKeyVal::new(
+                    "peers",
+                    LinkedNode::Vector(LinkedVector {
+                        min: 1,
+                        max: 2,
+                        ty: Box::new(LinkedNode::ForeignStruct("colors".into()))
+                    })
+                ),
+            ]
+        })
+    );
+
+    // A vector must be bounded
+    for cddl in [
+        "a = { b: [ * uint .size 2 ] }",
+        "a = { b: [ 1* uint .size 2 ] }",
+    ] {
+        let cddl = parse_cddl(cddl).unwrap();
+        assert_eq!(
+            flatten(&cddl).and_then(|node| link(&node)),
+            Err(ValidateError::InvalidArraySize)
+        );
+    }
+
+    // A vector of strings is not supported
+    let node = flatten(&parse_cddl("a = { b: [ 0*2 tstr .size 4 ] }").unwrap()).unwrap();
+    assert_eq!(link(&node), Err(ValidateError::InvalidArray));
+
+    // A vector may be optional
+    let node = flatten(&parse_cddl("a = { ? ids: [ 0*4 uint .size 2 ] }").unwrap()).unwrap();
+    assert_eq!(
+        link(&node).unwrap()["a"],
+        LinkedNode::Struct(Fields {
+            members: vec![LinkedKeyVal::new(
+                "ids",
+                LinkedNode::Optional(Box::new(LinkedNode::Vector(LinkedVector {
+                    min: 0,
+                    max: 4,
+                    ty: Box::new(ConstrainedType::U16.into())
+                })))
+            )]
+        })
+    );
+}
+
+#[test]
+fn parse_array() {
+    let cddl = parse_cddl(&read_cddl("test.cddl")).unwrap();
+    let node = flatten(&cddl).unwrap();
+    let linked = link(&node).unwrap();
+
+    // A linked struct with arrays of primatives, strings and structs
+    assert_eq!(
+        linked["table"],
+        LinkedNode::Struct(Fields {
+            members: vec![
+                LinkedKeyVal::new(
+                    "gains",
+                    LinkedNode::Array(LinkedArray {
+                        len: 4,
+                        ty: Box::new(alias("u16", ConstrainedType::U16.into()))
+                    })
+                ),
+                LinkedKeyVal::new(
+                    "names",
+                    LinkedNode::Array(LinkedArray {
+                        len: 2,
+                        ty: Box::new(ConstrainedType::Str(8).into())
+                    })
+                ),
+                LinkedKeyVal::new(
+                    "peers",
+                    LinkedNode::Array(LinkedArray {
+                        len: 2,
+                        ty: Box::new(LinkedNode::ForeignStruct("colors".into()))
+                    })
+                ),
+            ]
+        })
+    );
+
+    // An array or vector named by a rule is referred to by it's name
+    let cddl = r#"
+        a = { b: b, ? c: c }
+        b = [ 2*2 bool ]
+        c = [ 0*4 bool ]
+    "#;
+    let node = flatten(&parse_cddl(cddl).unwrap()).unwrap();
+    assert_eq!(
+        link(&node).unwrap()["a"],
+        LinkedNode::Struct(Fields {
+            members: vec![
+                LinkedKeyVal::new(
+                    "b",
+                    alias(
+                        "b",
+                        LinkedNode::Array(LinkedArray {
+                            len: 2,
+                            ty: Box::new(ConstrainedType::Bool.into())
+                        })
+                    )
+                ),
+                LinkedKeyVal::new(
+                    "c",
+                    LinkedNode::Optional(Box::new(alias(
+                        "c",
+                        LinkedNode::Vector(LinkedVector {
+                            min: 0,
+                            max: 4,
+                            ty: Box::new(ConstrainedType::Bool.into())
+                        })
+                    )))
+                ),
+            ]
+        })
+    );
+
+    // A group named by a rule is a struct of it's own when it is the type of a member
+    let cddl = r#"
+        a = { b: b, ? c: b }
+        b = ( d: bool )
+    "#;
+    let node = flatten(&parse_cddl(cddl).unwrap()).unwrap();
+    let linked = link(&node).unwrap();
+    assert_eq!(
+        linked["a"],
+        LinkedNode::Struct(Fields {
+            members: vec![
+                LinkedKeyVal::new("b", LinkedNode::ForeignStruct("b".into())),
+                LinkedKeyVal::new(
+                    "c",
+                    LinkedNode::Optional(Box::new(LinkedNode::ForeignStruct("b".into())))
+                ),
+            ]
+        })
+    );
+    for language in [Language::C, Language::Rust, Language::Typescript] {
+        let opts = Options {
+            language,
+            ..Options::default()
+        };
+        assert!(render_lib(cddl, &opts).is_ok());
+    }
+
+    // Every entry of an array is stored, so an array of literals is invalid
+    let node = flatten(&parse_cddl("a = { b: [ 2*2 1 ] }").unwrap()).unwrap();
+    assert_eq!(link(&node), Err(ValidateError::InvalidArray));
+}
+
+#[test]
+fn parse_nested_array() {
+    let cddl = parse_cddl(&read_cddl("test.cddl")).unwrap();
+    let node = flatten(&cddl).unwrap();
+    let linked = link(&node).unwrap();
+
+    // A linked struct with a nested array
+    assert_eq!(
+        linked["grid"],
+        LinkedNode::Struct(Fields {
+            members: vec![LinkedKeyVal::new(
+                "cells",
+                LinkedNode::Array(LinkedArray {
+                    len: 2,
+                    ty: Box::new(LinkedNode::Array(LinkedArray {
+                        len: 3,
+                        ty: Box::new(alias("i16", ConstrainedType::I16.into()))
+                    }))
+                })
+            )]
+        })
+    );
+
+    // Only arrays of a fixed size may be nested
+    let node = flatten(&parse_cddl("a = { b: [ 2*2 [ 0*3 bool ] ] }").unwrap()).unwrap();
+    assert_eq!(link(&node), Err(ValidateError::InvalidArray));
+}
+
+#[test]
+fn parse_unconstrained() {
+    let cddl = parse_cddl(&read_cddl("test.cddl")).unwrap();
+    let node = flatten(&cddl).unwrap();
+    let linked = link(&node).unwrap();
+
+    // A linked struct with unconstrained members
+    assert_eq!(
+        linked["profile"],
+        LinkedNode::Struct(Fields {
+            members: vec![
+                LinkedKeyVal::new("name", ConstrainedType::TStr.into()),
+                LinkedKeyVal::new("avatar", ConstrainedType::BStr.into()),
+                LinkedKeyVal::new("visits", ConstrainedType::UInt.into()),
+                LinkedKeyVal::new("offset", ConstrainedType::Int.into()),
+            ]
+        })
+    );
+
+    // Entries of a vector are not byte strings
+    let node = flatten(&parse_cddl("a = { b: [ 0*2 bstr ] }").unwrap()).unwrap();
+    assert_eq!(link(&node), Err(ValidateError::InvalidArray));
+}
+
+#[test]
+fn parse_float() {
+    let cddl = parse_cddl(&read_cddl("test.cddl")).unwrap();
+    let node = flatten(&cddl).unwrap();
+    let linked = link(&node).unwrap();
+
+    // A linked struct with float members
+    assert_eq!(
+        linked["reading"],
+        LinkedNode::Struct(Fields {
+            members: vec![
+                LinkedKeyVal::new("temp", ConstrainedType::F32.into()),
+                LinkedKeyVal::new("humidity", ConstrainedType::F16.into()),
+                LinkedKeyVal::new("pressure", ConstrainedType::F64.into()),
+            ]
+        })
+    );
+
+    // Entries of a vector are encoded with their own impls, which a float16 does not have
+    let node = flatten(&parse_cddl("a = { b: [ 0*2 float16 ] }").unwrap()).unwrap();
+    assert_eq!(link(&node), Err(ValidateError::InvalidArray));
+}
+
+#[test]
+fn parse_tagged() {
+    let cddl = r#"
+        event = {
+            at: #6.1(uint .size 4),
+            id: #6.37(bstr .size 16),
+            ? origin: #6.1000(colors),
+        }
+        colors = { car: uint .size 1 }
+    "#;
+    let node = flatten(&parse_cddl(&crate::util::desugar_tags(cddl)).unwrap()).unwrap();
+    let linked = crate::parse(cddl).unwrap();
+
+    // A flattened tagged member
+    assert_eq!(
+        node["event"],
+        Node::Map(Group {
+            members: vec![
+                KeyVal::new(
+                    "at",
+                    Node::Tagged(Tagged {
+                        tag: 1,
+                        ty: Box::new(ConstrainedType::U32.into())
+                    })
+                )
+                .into(),
+                KeyVal::new(
+                    "id",
+                    Node::Tagged(Tagged {
+                        tag: 37,
+                        ty: Box::new(ConstrainedType::Bytes(16).into())
+                    })
+                )
+                .into(),
+                KeyVal::new(
+                    "origin",
+                    Node::Optional(Box::new(Node::Tagged(Tagged {
+                        tag: 1000,
+                        ty: Box::new(Node::Foreign("colors".into()))
+                    })))
+                )
+                .into(),
+            ]
+        })
+    );
+
+    // A linked struct with tagged members, a byte string of a fixed size is an array of bytes
+    assert_eq!(
+        linked["event"],
+        LinkedNode::Struct(Fields {
+            members: vec![
+                LinkedKeyVal::new(
+                    "at",
+                    LinkedNode::Tagged(LinkedTagged {
+                        tag: 1,
+                        ty: Box::new(ConstrainedType::U32.into())
+                    })
+                ),
+                LinkedKeyVal::new(
+                    "id",
+                    LinkedNode::Tagged(LinkedTagged {
+                        tag: 37,
+                        ty: Box::new(LinkedNode::Array(LinkedArray {
+                            len: 16,
+                            ty: Box::new(ConstrainedType::U8.into())
+                        }))
+                    })
+                ),
+                LinkedKeyVal::new(
+                    "origin",
+                    LinkedNode::Optional(Box::new(LinkedNode::Tagged(LinkedTagged {
+                        tag: 1000,
+                        ty: Box::new(LinkedNode::ForeignStruct("colors".into()))
+                    })))
+                ),
+            ]
+        })
+    );
+
+    // The bounds of a range are not checked behind a tag
+    assert_eq!(
+        crate::parse("a = { b: #6.1(0..9) }"),
+        Err(ValidateError::InvalidTag)
+    );
+}
+
+#[test]
+fn parse_desugar_tags() {
+    assert_eq!(
+        crate::util::desugar_tags("a = #6.1(uint) ; #6.2(uint)"),
+        "a = (uint) .tag 1 ; #6.2(uint)"
+    );
+    assert_eq!(
+        crate::util::desugar_tags("a = { b: #6.24(bstr .cbor (c)), d: \"#6.3(x)\" }"),
+        "a = { b: (bstr .cbor (c)) .tag 24, d: \"#6.3(x)\" }"
+    );
+    assert_eq!(
+        crate::util::desugar_tags("a = #6.1(#6.2(uint))"),
+        "a = ((uint) .tag 2) .tag 1"
+    );
+}
+
+#[test]
+fn parse_prelude() {
+    let cddl = r#"
+        record = {
+            created: tdate,
+            updated: time,
+            reserved: null,
+            extra: any,
+        }
+        uri = { scheme: tstr .size 8 }
+    "#;
+    let c = crate::parse_for(cddl, &Language::C).unwrap();
+    let rust = crate::parse_for(cddl, &Language::Rust).unwrap();
+
+    // A tdate is a fixed string in C, and a String when we may own memory
+    let member =
+        |nodes: &std::collections::BTreeMap<String, LinkedNode>, n: usize| match &nodes["record"] {
+            LinkedNode::Struct(fields) => fields.members[n].1.clone(),
+            _ => unreachable!(),
+        };
+    let tdate = |ty: ConstrainedType| {
+        LinkedNode::Tagged(LinkedTagged {
+            tag: 0,
+            ty: Box::new(ty.into()),
+        })
+    };
+    assert_eq!(member(&c, 0), tdate(ConstrainedType::Str(36)));
+    assert_eq!(member(&rust, 0), tdate(ConstrainedType::TStr));
+
+    // Other prelude types are the same for every language
+    let time = LinkedNode::Tagged(LinkedTagged {
+        tag: 1,
+        ty: Box::new(ConstrainedType::I64.into()),
+    });
+    assert_eq!(member(&c, 1), time);
+    assert_eq!(member(&rust, 1), time);
+    assert_eq!(member(&c, 2), LinkedNode::Literal(Literal::Null));
+    assert_eq!(member(&rust, 3), ConstrainedType::Any.into());
+
+    // A rule takes precedence over the prelude, and the prelude is not part of the result
+    assert_eq!(rust.len(), 2);
+    assert!(matches!(rust["uri"], LinkedNode::Struct(_)));
+    assert_eq!(
+        crate::parse("a = { b: unknown }"),
+        Err(ValidateError::ForeignKey("unknown".into()))
+    );
+}
+
+#[test]
+fn parse_map_keys() {
+    let cddl = r#"
+        a = {
+            1: uint .size 1,
+            ? -2 => uint .size 2,
+            "content-type": tstr .size 8,
+            "2nd" => bool,
+            name: bool,
+        }
+    "#;
+    let node = flatten(&parse_cddl(cddl).unwrap()).unwrap();
+    let linked = link(&node).unwrap();
+
+    // Integer keys are named after their value, and text keys are sanitised
+    assert_eq!(
+        node["a"],
+        Node::Map(Group {
+            members: vec![
+                KeyVal::keyed("key-1", ConstrainedType::U8.into(), Literal::UInt(1)).into(),
+                KeyVal::keyed(
+                    "key-neg-2",
+                    Node::Optional(Box::new(ConstrainedType::U16.into())),
+                    Literal::Int(-2)
+                )
+                .into(),
+                KeyVal::keyed(
+                    "content-type",
+                    ConstrainedType::Str(8).into(),
+                    Literal::Str("content-type".into())
+                )
+                .into(),
+                KeyVal::keyed(
+                    "key-2nd",
+                    ConstrainedType::Bool.into(),
+                    Literal::Str("2nd".into())
+                )
+                .into(),
+                KeyVal::new("name", ConstrainedType::Bool.into()).into(),
+            ]
+        })
+    );
+
+    // The key on the wire is carried into the linked struct
+    let wire = match &linked["a"] {
+        LinkedNode::Struct(fields) => fields
+            .members
+            .iter()
+            .map(|LinkedKeyVal(_, _, wire)| wire.clone())
+            .collect::<Vec<Option<Literal>>>(),
+        _ => unreachable!(),
+    };
+    assert_eq!(
+        wire,
+        vec![
+            Some(Literal::UInt(1)),
+            Some(Literal::Int(-2)),
+            Some(Literal::Str("content-type".into())),
+            Some(Literal::Str("2nd".into())),
+            None,
+        ]
+    );
+
+    // Other keys are not supported
+    let cddl = parse_cddl("a = { 1.5: bool }").unwrap();
+    assert_eq!(
+        flatten(&cddl),
+        Err(ValidateError::UnsupportedCddl("map key".into()))
+    );
+}
+
+#[test]
+fn parse_record() {
+    let cddl = r#"
+        point = [ x: int .size 2, ? label: tstr .size 8 ]
+        pair = [ 1, uint .size 2, ? point ]
+    "#;
+    let node = flatten(&parse_cddl(cddl).unwrap()).unwrap();
+    let linked = link(&node).unwrap();
+
+    // A record of named entries
+    assert_eq!(
+        node["point"],
+        Node::Record(Group {
+            members: vec![
+                KeyVal::new("x", ConstrainedType::I16.into()).into(),
+                KeyVal::new(
+                    "label",
+                    Node::Optional(Box::new(ConstrainedType::Str(8).into()))
+                )
+                .into(),
+            ]
+        })
+    );
+
+    // Unnamed entries are named after their position, literals are not counted
+    assert_eq!(
+        linked["pair"],
+        LinkedNode::Record(Fields {
+            members: vec![
+                LinkedKeyVal::new("literal-0", LinkedNode::Literal(Literal::UInt(1))),
+                LinkedKeyVal::new("0", ConstrainedType::U16.into()),
+                LinkedKeyVal::new(
+                    "1",
+                    LinkedNode::Optional(Box::new(LinkedNode::ForeignStruct("point".into())))
+                ),
+            ]
+        })
+    );
+
+    // Entries are either all named or all unnamed
+    let cddl = parse_cddl("a = [ x: uint .size 2, uint .size 2 ]").unwrap();
+    assert_eq!(
+        flatten(&cddl),
+        Err(ValidateError::UnsupportedCddl(
+            "record of named and unnamed entries".into()
+        ))
+    );
+
+    // A record is a type of it's own, so it may not be declared inline
+    let node = flatten(&parse_cddl("a = { b: [ 2*2 [ uint .size 1, bool ] ] }").unwrap()).unwrap();
+    assert_eq!(link(&node), Err(ValidateError::InvalidArray));
+}
+
+#[test]
+fn parse_recursive() {
+    let cddl = r#"
+        tree = { ? left: tree, children: [ 0*4 tree ] }
+        a = { b: b }
+        b = { ? a: a }
+    "#;
+    let node = flatten(&parse_cddl(cddl).unwrap()).unwrap();
+    let linked = link(&node).unwrap();
+
+    // A struct refers to itself through an optional member or a vector that may be empty
+    let boxed = || LinkedNode::Boxed(Box::new(LinkedNode::ForeignStruct("tree".into())));
+    assert_eq!(
+        linked["tree"],
+        LinkedNode::Struct(Fields {
+            members: vec![
+                LinkedKeyVal::new("left", LinkedNode::Optional(Box::new(boxed()))),
+                LinkedKeyVal::new(
+                    "children",
+                    LinkedNode::Vector(LinkedVector {
+                        min: 0,
+                        max: 4,
+                        ty: Box::new(boxed()),
+                    })
+                ),
+            ]
+        })
+    );
+
+    // Only the reference that may be absent is boxed
+    assert_eq!(
+        linked["a"],
+        LinkedNode::Struct(Fields {
+            members: vec![LinkedKeyVal::new(
+                "b",
+                LinkedNode::ForeignStruct("b".into())
+            )]
+        })
+    );
+    assert_eq!(
+        linked["b"],
+        LinkedNode::Struct(Fields {
+            members: vec![LinkedKeyVal::new(
+                "a",
+                LinkedNode::Optional(Box::new(LinkedNode::Boxed(Box::new(
+                    LinkedNode::ForeignStruct("a".into())
+                ))))
+            )]
+        })
+    );
+
+    // A type that always contains itself is infinite
+    let cddl = r#"
+        a = { b: b }
+        b = { c: [ 1*2 a ] }
+    "#;
+    let node = flatten(&parse_cddl(cddl).unwrap()).unwrap();
+    assert_eq!(
+        link(&node),
+        Err(ValidateError::Cycle(vec![
+            "a".into(),
+            "b".into(),
+            "a".into()
+        ]))
+    );
+    let node = flatten(&parse_cddl("g = ( x: bool, g )").unwrap()).unwrap();
+    assert_eq!(
+        link(&node),
+        Err(ValidateError::Cycle(vec!["g".into(), "g".into()]))
+    );
+}
+
+#[test]
+fn parse_inline_map() {
+    let cddl = r#"
+        a = { b: { c: bool, ? d: { e: bool } }, ? f: [ x: bool ] }
+    "#;
+    let node = flatten(&parse_cddl(cddl).unwrap()).unwrap();
+    let linked = link(&node).unwrap();
+
+    // Inline maps are hoisted into rules named after the member that holds them
+    assert_eq!(
+        linked["a"],
+        LinkedNode::Struct(Fields {
+            members: vec![
+                LinkedKeyVal::new("b", LinkedNode::ForeignStruct("a-b".into())),
+                LinkedKeyVal::new(
+                    "f",
+                    LinkedNode::Optional(Box::new(LinkedNode::ForeignStruct("a-f".into())))
+                ),
+            ]
+        })
+    );
+    assert_eq!(
+        linked["a-b"],
+        LinkedNode::Struct(Fields {
+            members: vec![
+                LinkedKeyVal::new("c", ConstrainedType::Bool.into()),
+                LinkedKeyVal::new(
+                    "d",
+                    LinkedNode::Optional(Box::new(LinkedNode::ForeignStruct("a-b-d".into())))
+                ),
+            ]
+        })
+    );
+    assert_eq!(
+        linked["a-b-d"],
+        LinkedNode::Struct(Fields {
+            members: vec![LinkedKeyVal::new("e", ConstrainedType::Bool.into())]
+        })
+    );
+    assert_eq!(
+        linked["a-f"],
+        LinkedNode::Record(Fields {
+            members: vec![LinkedKeyVal::new("x", ConstrainedType::Bool.into())]
+        })
+    );
+
+    // The name of a hoisted map may not be the name of another rule
+    let node = flatten(&parse_cddl("a = { b: { c: bool } }\na-b = bool").unwrap()).unwrap();
+    assert_eq!(link(&node), Err(ValidateError::DuplicateRule("a-b".into())));
+}
+
+#[test]
+fn parse_dictionary() {
+    let cddl = r#"
+        a = { * tstr .size 8 => uint .size 2 }
+        b = { id: uint .size 1, slots: { 1*4 uint .size 1 => a } }
+    "#;
+    let node = flatten(&parse_cddl(cddl).unwrap()).unwrap();
+    assert_eq!(
+        node["a"],
+        Node::Dictionary(Dictionary {
+            min: 0,
+            max: usize::MAX,
+            key: Box::new(ConstrainedType::Str(8).into()),
+            val: Box::new(ConstrainedType::U16.into()),
+        })
+    );
+    let linked = link(&node).unwrap();
+    assert_eq!(
+        linked["a"],
+        LinkedNode::Dictionary(LinkedDictionary {
+            min: 0,
+            max: usize::MAX,
+            key: Box::new(ConstrainedType::Str(8).into()),
+            val: Box::new(ConstrainedType::U16.into()),
+        })
+    );
+
+    // An inline dictionary is hoisted like an inline map
+    assert_eq!(
+        linked["b"],
+        LinkedNode::Struct(Fields {
+            members: vec![
+                LinkedKeyVal::new("id", ConstrainedType::U8.into()),
+                LinkedKeyVal::new("slots", LinkedNode::ForeignStruct("b-slots".into())),
+            ]
+        })
+    );
+    assert_eq!(
+        linked["b-slots"],
+        LinkedNode::Dictionary(LinkedDictionary {
+            min: 1,
+            max: 4,
+            key: Box::new(ConstrainedType::U8.into()),
+            val: Box::new(LinkedNode::ForeignStruct("a".into())),
+        })
+    );
+
+    // A key is an integer or text, and a value is not optional
+    let node = flatten(&parse_cddl("a = { * bool => uint }").unwrap()).unwrap();
+    assert_eq!(link(&node), Err(ValidateError::InvalidDictionary));
+    let node = flatten(&parse_cddl("a = { * tstr => { b: uint } }").unwrap()).unwrap();
+    assert_eq!(link(&node), Err(ValidateError::InvalidDictionary));
+    assert_eq!(
+        flatten(&parse_cddl("a = { 2*1 tstr => uint }").unwrap()),
+        Err(ValidateError::InvalidArraySize)
+    );
+}
+
+#[test]
+fn parse_control() {
+    let cddl = r#"
+        small = (uint .size 1) .le 100
+        nonzero = (uint .size 2) .ne 0
+        flags = (uint .size 1) .bits &( fin: 0, syn: 1 )
+        named = (uint .size 2) .bits tcp
+        tcp = &( fin: 0, ack: 4 )
+        port = (uint .size 2) .default 80
+        serial = (tstr .size 8) .regexp "[0-9]+"
+    "#;
+    let node = flatten(&parse_cddl(cddl).unwrap()).unwrap();
+    assert_eq!(
+        node["port"],
+        Node::Control(Control {
+            ty: Box::new(ConstrainedType::U16.into()),
+            op: ControlOp::Default,
+            arg: Box::new(Node::Literal(Literal::UInt(80))),
+        })
+    );
+    let linked = link(&node).unwrap();
+
+    // A comparison narrows the bounds of an integer, which keeps it's width
+    assert_eq!(
+        linked["small"],
+        LinkedNode::Range(LinkedRange {
+            ty: ConstrainedType::U8,
+            min: Literal::UInt(0),
+            max: Literal::UInt(100),
+            ne: None,
+        })
+    );
+    assert_eq!(
+        linked["nonzero"],
+        LinkedNode::Range(LinkedRange {
+            ty: ConstrainedType::U16,
+            min: Literal::UInt(0),
+            max: Literal::UInt(65535),
+            ne: Some(Literal::UInt(0)),
+        })
+    );
+
+    // Bits are named by an inline choice or by a rule
+    assert_eq!(
+        linked["flags"],
+        LinkedNode::Bits(LinkedBits {
+            ty: ConstrainedType::U8,
+            flags: vec![("fin".into(), 0), ("syn".into(), 1)],
+        })
+    );
+    assert_eq!(
+        linked["named"],
+        LinkedNode::Bits(LinkedBits {
+            ty: ConstrainedType::U16,
+            flags: vec![("fin".into(), 0), ("ack".into(), 4)],
+        })
+    );
+    assert_eq!(
+        linked["port"],
+        LinkedNode::Default(LinkedDefault {
+            ty: Box::new(ConstrainedType::U16.into()),
+            value: Literal::UInt(80),
+        })
+    );
+    assert_eq!(
+        linked["serial"],
+        LinkedNode::Regexp(LinkedRegexp {
+            ty: ConstrainedType::Str(8),
+            pattern: "[0-9]+".into(),
+        })
+    );
+
+    // A control must agree with it's type and argument
+    for cddl in ["a = (uint .size 1) .gt 255", "a = (uint .size 1) .lt 0"] {
+        let node = flatten(&parse_cddl(cddl).unwrap()).unwrap();
+        assert_eq!(link(&node), Err(ValidateError::InvalidRange));
+    }
+    for cddl in [
+        "a = (uint .size 1) .bits &( a: 8 )",
+        "a = (uint .size 1) .default 256",
+        "a = (tstr .size 2) .default \"abc\"",
+        "a = tstr .regexp \"[\"",
+    ] {
+        let node = flatten(&parse_cddl(cddl).unwrap()).unwrap();
+        assert_eq!(link(&node), Err(ValidateError::InvalidControlArg));
+    }
+    for cddl in [
+        "a = bool .lt 1",
+        "a = int .bits &( a: 0 )",
+        "a = uint .regexp \"a\"",
+    ] {
+        let node = flatten(&parse_cddl(cddl).unwrap()).unwrap();
+        assert_eq!(link(&node), Err(ValidateError::InvalidControl));
+    }
+}
+
+#[test]
+fn parse_embedded() {
+    let cddl = r#"
+        envelope = {
+            payload: bstr .cbor msg,
+            signed: #6.24((bstr .size 32) .cbor msg),
+            ? forward: bstr .cborseq envelope,
+        }
+        msg = { id: uint .size 1 }
+    "#;
+    let node = flatten(&parse_cddl(&crate::util::desugar_tags(cddl)).unwrap()).unwrap();
+    let linked = link(&node).unwrap();
+
+    // An encoded value is hoisted like an inline map, and may refer back to the struct that
+    // holds it without a box as it's bytes are stored
+    assert_eq!(
+        linked["envelope"],
+        LinkedNode::Struct(Fields {
+            members: vec![
+                LinkedKeyVal::new(
+                    "payload",
+                    LinkedNode::ForeignStruct("envelope-payload".into())
+                ),
+                LinkedKeyVal::new(
+                    "signed",
+                    LinkedNode::ForeignStruct("envelope-signed".into())
+                ),
+                LinkedKeyVal::new(
+                    "forward",
+                    LinkedNode::Optional(Box::new(LinkedNode::ForeignStruct(
+                        "envelope-forward".into()
+                    )))
+                ),
+            ]
+        })
+    );
+    assert_eq!(
+        linked["envelope-payload"],
+        LinkedNode::Embedded(LinkedEmbedded {
+            ty: Box::new(LinkedNode::ForeignStruct("msg".into())),
+            max: usize::MAX,
+            seq: false,
+            tag: None,
+        })
+    );
+    assert_eq!(
+        linked["envelope-signed"],
+        LinkedNode::Embedded(LinkedEmbedded {
+            ty: Box::new(LinkedNode::ForeignStruct("msg".into())),
+            max: 32,
+            seq: false,
+            tag: Some(24),
+        })
+    );
+    assert_eq!(
+        linked["envelope-forward"],
+        LinkedNode::Embedded(LinkedEmbedded {
+            ty: Box::new(LinkedNode::ForeignStruct("envelope".into())),
+            max: usize::MAX,
+            seq: true,
+            tag: None,
+        })
+    );
+
+    // The bytes hold a value of a type named by a rule
+    let node = flatten(&parse_cddl("a = tstr .cbor b\nb = { c: bool }").unwrap()).unwrap();
+    assert_eq!(link(&node), Err(ValidateError::InvalidControl));
+    let node = flatten(&parse_cddl("a = bstr .cbor uint").unwrap()).unwrap();
+    assert_eq!(link(&node), Err(ValidateError::InvalidControlArg));
+}
+
+#[test]
+fn parse_literal() {
+    let cddl = r#"
+        big = 18446744073709551615
+        small = -40
+        initial = "C"
+        magic = 'ab'
+        key = h'DEADBEEF'
+        config = { version: 2, kind: "config", magic: 'MB', signed: true, name: tstr }
+    "#;
+    let linked = link(&flatten(&parse_cddl(cddl).unwrap()).unwrap()).unwrap();
+
+    // A literal keeps the width of it's value
+    assert_eq!(linked["big"], LinkedNode::Literal(Literal::UInt(u64::MAX)));
+    assert_eq!(linked["small"], LinkedNode::Literal(Literal::Int(-40)));
+
+    // Text of one character is still text
+    assert_eq!(
+        linked["initial"],
+        LinkedNode::Literal(Literal::Str("C".into()))
+    );
+
+    // Byte strings are bytes, whether written as text or hex
+    assert_eq!(
+        linked["magic"],
+        LinkedNode::Literal(Literal::Bytes(b"ab".to_vec()))
+    );
+    assert_eq!(
+        linked["key"],
+        LinkedNode::Literal(Literal::Bytes(vec![0xde, 0xad, 0xbe, 0xef]))
+    );
+
+    // A literal member is linked as it's value, so it is written and checked but not stored
+    assert_eq!(
+        linked["config"],
+        LinkedNode::Struct(Fields {
+            members: vec![
+                LinkedKeyVal::new("version", LinkedNode::Literal(Literal::UInt(2))),
+                LinkedKeyVal::new("kind", LinkedNode::Literal(Literal::Str("config".into()))),
+                LinkedKeyVal::new("magic", LinkedNode::Literal(Literal::Bytes(b"MB".to_vec()))),
+                LinkedKeyVal::new("signed", LinkedNode::Literal(Literal::Bool(true))),
+                LinkedKeyVal::new("name", ConstrainedType::TStr.into()),
+            ]
+        })
+    );
+}
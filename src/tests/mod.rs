@@ -1,2 +1,3 @@
+mod gen;
 mod ivt;
 mod util;
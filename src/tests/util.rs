@@ -1,3 +1,4 @@
+use crate::{LinkedAlias, LinkedNode};
 use std::fs;
 use std::io::prelude::*;
 use std::path::PathBuf;
@@ -62,3 +63,11 @@ pub fn read_cddl(path: &str) -> String {
     file.read_to_string(&mut cddl).unwrap();
     cddl
 }
+
+/// A reference to a rule that names a type
+pub fn alias(name: &str, ty: LinkedNode) -> LinkedNode {
+    LinkedNode::Alias(LinkedAlias {
+        name: name.into(),
+        ty: Box::new(ty),
+    })
+}
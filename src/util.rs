@@ -13,9 +13,15 @@ pub enum ValidateError {
     InvalidType,
     InvalidArray,
     InvalidArraySize,
-    TodoEnums,
+    InvalidDictionary,
+    InvalidChoice,
+    InvalidRange,
+    InvalidTag,
     UnsupportedCddl(String),
     ForeignKey(String),
+    Cycle(Vec<String>),
+    InvalidRecursion,
+    DuplicateRule(String),
     Infallible,
 }
 impl fmt::Display for ValidateError {
@@ -26,15 +32,25 @@ impl fmt::Display for ValidateError {
             InvalidEnum0 => write!(f, "Enum type with 0 members unsupported"),
             InvalidUnconstrainedPrimative => write!(f, "type must be constrained"),
             InvalidLiteral => write!(f, "invalid literal"),
-            InvalidControl => write!(f, "size control only supported on primative types"),
-            InvalidControlArg => write!(f, "only integers supported for control args"),
+            InvalidControl => write!(f, "control operator not supported on this type"),
+            InvalidControlArg => write!(f, "invalid argument of a control operator"),
             InvalidGroupMissingKey => write!(f, "all group members must have a key"),
             InvalidType => write!(f, "invalid type"),
             InvalidArray => write!(f, "invalid array"),
             InvalidArraySize => write!(f, "invalid array size"),
-            TodoEnums => write!(f, "enums not supported"),
+            InvalidDictionary => write!(f, "invalid dictionary"),
+            InvalidChoice => write!(f, "invalid choice"),
+            InvalidRange => write!(f, "range bounds must be integers with min <= max"),
+            InvalidTag => write!(f, "invalid tag"),
             UnsupportedCddl(cddl) => write!(f, "CDDL not supported {}", cddl),
             ForeignKey(key) => write!(f, "foreign key not defined [{}]", key),
+            Cycle(path) => write!(
+                f,
+                "type refers to itself without an optional member or vector [{}]",
+                path.join(" -> ")
+            ),
+            InvalidRecursion => write!(f, "recursive types are only supported in rust"),
+            DuplicateRule(key) => write!(f, "inline map is named after another rule [{}]", key),
             Infallible => write!(f, "infallible"),
         }
     }
@@ -47,3 +63,74 @@ impl From<ParseError> for ValidateError {
 }
 
 impl error::Error for ValidateError {}
+
+/// The parser does not understand tagged types, so we rewrite every #6.n(type) to the control
+/// (type) .tag n, which it does. Strings and comments are left as they are
+pub(crate) fn desugar_tags(cddl: &str) -> String {
+    let src = cddl.as_bytes();
+    let mut out = String::with_capacity(cddl.len());
+    // The tag of every open #6.n( and the depth of parenthesis it was opened at
+    let mut tags: Vec<(&str, usize)> = Vec::new();
+    let mut depth = 0;
+    let mut i = 0;
+    while i < src.len() {
+        let next = match src[i] {
+            b'"' | b'\'' => string_end(src, i),
+            b';' => src[i..]
+                .iter()
+                .position(|c| *c == b'\n')
+                .map_or(src.len(), |n| i + n),
+            b'#' if src[i..].starts_with(b"#6.") => {
+                let digits = src[i + 3..]
+                    .iter()
+                    .take_while(|c| c.is_ascii_digit())
+                    .count();
+                let open = i + 3 + digits;
+                if digits > 0 && src.get(open) == Some(&b'(') {
+                    tags.push((&cddl[i + 3..open], depth));
+                    depth += 1;
+                    out.push('(');
+                    i = open + 1;
+                    continue;
+                }
+                i + 1
+            }
+            b'(' => {
+                depth += 1;
+                i + 1
+            }
+            b')' => {
+                depth = depth.saturating_sub(1);
+                out.push(')');
+                match tags.last() {
+                    Some((tag, at)) if *at == depth => {
+                        out.push_str(" .tag ");
+                        out.push_str(tag);
+                        tags.pop();
+                    }
+                    _ => {}
+                }
+                i += 1;
+                continue;
+            }
+            _ => i + cddl[i..].chars().next().map_or(1, char::len_utf8),
+        };
+        out.push_str(&cddl[i..next]);
+        i = next;
+    }
+    out
+}
+
+/// The index after the closing quote of the string starting at i
+fn string_end(src: &[u8], i: usize) -> usize {
+    let quote = src[i];
+    let mut n = i + 1;
+    while n < src.len() {
+        match src[n] {
+            b'\\' => n += 2,
+            c if c == quote => return n + 1,
+            _ => n += 1,
+        }
+    }
+    src.len()
+}
@@ -1,7 +1,9 @@
 use crate::ivt::*;
 use crate::util;
+use heck::{ToSnakeCase, ToUpperCamelCase};
 use serde::{Deserialize, Serialize};
 use std::borrow::Cow;
+use std::cell::{Cell, RefCell};
 use std::collections::BTreeMap;
 use util::ValidateError;
 
@@ -18,12 +20,13 @@ pub struct Fields {
 pub struct LinkedKeyValStruct {
     key: String,
     val: LinkedNode,
+    wire: Option<Literal>,
 }
 
 /// When we serialize/deserialize we use struct form, and convert into tuple
 impl From<LinkedKeyValStruct> for LinkedKeyVal {
     fn from(node: LinkedKeyValStruct) -> LinkedKeyVal {
-        LinkedKeyVal(node.key, node.val)
+        LinkedKeyVal(node.key, node.val, node.wire)
     }
 }
 
@@ -33,6 +36,7 @@ impl From<LinkedKeyVal> for LinkedKeyValStruct {
         LinkedKeyValStruct {
             key: node.0,
             val: node.1,
+            wire: node.2,
         }
     }
 }
@@ -42,17 +46,30 @@ impl From<LinkedKeyVal> for LinkedKeyValStruct {
 /// NOTE LinkedKeyVal and LinkedArray could share same definition with
 #[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
 #[serde(from = "LinkedKeyValStruct", into = "LinkedKeyValStruct")]
-pub struct LinkedKeyVal(pub(crate) String, pub(crate) LinkedNode);
+pub struct LinkedKeyVal(
+    pub(crate) String,
+    pub(crate) LinkedNode,
+    pub(crate) Option<Literal>,
+);
 impl LinkedKeyVal {
     pub fn new<'a, K: Into<Cow<'a, str>>>(key: K, node: LinkedNode) -> LinkedKeyVal {
-        LinkedKeyVal(key.into().into(), node)
+        LinkedKeyVal(key.into().into(), node, None)
+    }
+
+    /// A member of a map written with a key other than it's name
+    pub fn keyed<'a, K: Into<Cow<'a, str>>>(
+        key: K,
+        node: LinkedNode,
+        wire: Literal,
+    ) -> LinkedKeyVal {
+        LinkedKeyVal(key.into().into(), node, Some(wire))
     }
 }
 
 /// Helper when creating Maps from Key/Value tuples.
 impl From<(String, LinkedNode)> for LinkedKeyVal {
     fn from(t: (String, LinkedNode)) -> LinkedKeyVal {
-        LinkedKeyVal(t.0, t.1)
+        LinkedKeyVal(t.0, t.1, None)
     }
 }
 
@@ -66,6 +83,101 @@ pub struct LinkedArray {
     pub ty: Box<LinkedNode>,
 }
 
+/// A linked vector, similiar to ivt::Vector
+#[derive(Clone, Debug, PartialEq, Serialize, Deserialize)]
+pub struct LinkedVector {
+    pub min: usize,
+    pub max: usize,
+    pub ty: Box<LinkedNode>,
+}
+
+/// A linked dictionary, similiar to ivt::Dictionary. The key is the type an alias of the key
+/// names, so that keys can be compared
+#[derive(Clone, Debug, PartialEq, Serialize, Deserialize)]
+pub struct LinkedDictionary {
+    pub min: usize,
+    pub max: usize,
+    pub key: Box<LinkedNode>,
+    pub val: Box<LinkedNode>,
+}
+
+/// A linked choice, similiar to ivt::Choice, except each variant is named after the rule it
+/// references so that it can be rendered as an enum variant
+#[derive(Clone, Debug, PartialEq, Serialize, Deserialize)]
+pub struct LinkedChoice {
+    pub variants: Vec<LinkedKeyVal>,
+}
+
+/// A struct made of a group choice. Every variant is a group of fields that shares a literal
+/// discriminant member, and the value of the discriminant selects the variant when decoding
+#[derive(Clone, Debug, PartialEq, Serialize, Deserialize)]
+pub struct LinkedUnion {
+    /// The key of the literal member common to every variant
+    pub discriminant: String,
+    /// The variants, each is a LinkedNode::Fields named after it's discriminant
+    pub variants: Vec<LinkedKeyVal>,
+}
+
+/// A linked range, where the bounds are resolved and inclusive, and the type is the smallest
+/// integer that can hold every value of the range. A comparison control narrows the bounds of the
+/// integer type it constrains, and .ne excludes a single value
+#[derive(Clone, Debug, PartialEq, Serialize, Deserialize)]
+pub struct LinkedRange {
+    pub ty: ConstrainedType,
+    pub min: Literal,
+    pub max: Literal,
+    pub ne: Option<Literal>,
+}
+
+/// An unsigned integer of flags, each flag is a bit position named by the choice of the .bits
+/// control. Other bits may not be set
+#[derive(Clone, Debug, PartialEq, Serialize, Deserialize)]
+pub struct LinkedBits {
+    pub ty: ConstrainedType,
+    pub flags: Vec<(String, u64)>,
+}
+
+/// A type with the value of a .default control, which is the value of the type by default
+#[derive(Clone, Debug, PartialEq, Serialize, Deserialize)]
+pub struct LinkedDefault {
+    pub ty: Box<LinkedNode>,
+    pub value: Literal,
+}
+
+/// A text string that must match the pattern of a .regexp control. The pattern matches the whole
+/// text, as the patterns of CDDL are implicitly anchored
+#[derive(Clone, Debug, PartialEq, Serialize, Deserialize)]
+pub struct LinkedRegexp {
+    pub ty: ConstrainedType,
+    pub pattern: String,
+}
+
+/// A byte string that holds the encoding of a value of another type (.cbor), or of a sequence of
+/// values (.cborseq). The bytes are stored as they are, and decoded when the value is read. The
+/// byte string may be tagged (IE: #6.24(bstr .cbor inner)), and holds at most max bytes
+#[derive(Clone, Debug, PartialEq, Serialize, Deserialize)]
+pub struct LinkedEmbedded {
+    pub ty: Box<LinkedNode>,
+    pub max: usize,
+    pub seq: bool,
+    pub tag: Option<u64>,
+}
+
+/// A type behind a CBOR tag, a value without the tag (or with another tag) is invalid
+#[derive(Clone, Debug, PartialEq, Serialize, Deserialize)]
+pub struct LinkedTagged {
+    pub tag: u64,
+    pub ty: Box<LinkedNode>,
+}
+
+/// A reference to a rule that names another type, such as ip-addr = tstr .size 16. The name is
+/// kept so that the type can be rendered by it's name
+#[derive(Clone, Debug, PartialEq, Serialize, Deserialize)]
+pub struct LinkedAlias {
+    pub name: String,
+    pub ty: Box<LinkedNode>,
+}
+
 /// When we have an IVT node, we lookup unresolved types and build a complete tree
 #[derive(Clone, Debug, PartialEq, Serialize, Deserialize)]
 #[serde(rename_all = "lowercase")]
@@ -77,12 +189,44 @@ pub enum LinkedNode {
     ConstrainedType(ConstrainedType),
     /// An array is of a fixed size of a single type
     Array(LinkedArray),
+    /// An array of a single type with a variable number of entries up to a bound
+    Vector(LinkedVector),
+    /// A map of entries of one key type and one value type (Can only exist at top level)
+    Dictionary(LinkedDictionary),
     /// A group of fields missing context (might be a struct)
     Fields(Fields),
     /// A fully qualified struct with fields (Can only exist at top level)
     Struct(Fields),
+    /// A struct encoded as an array of it's fields in order of declaration (Can only exist at top
+    /// level)
+    Record(Fields),
     /// If a struct contains a nested struct, we store flatten instead of nest
     ForeignStruct(String),
+    /// A tagged union of types (Can only exist at top level)
+    Choice(LinkedChoice),
+    /// If a struct contains a choice, we store flatten instead of nest
+    ForeignChoice(String),
+    /// A struct with a group choice (Can only exist at top level)
+    Union(LinkedUnion),
+    /// An integer type that is checked to be within bounds
+    Range(LinkedRange),
+    /// An unsigned integer of named flags (Can only exist at top level)
+    Bits(LinkedBits),
+    /// A type with a value by default
+    Default(LinkedDefault),
+    /// A text string that matches a pattern (Can only exist at top level)
+    Regexp(LinkedRegexp),
+    /// A byte string of an encoded value (Can only exist at top level)
+    Embedded(LinkedEmbedded),
+    /// A struct member that may be omitted
+    Optional(Box<LinkedNode>),
+    /// A reference to a struct or choice that refers back to the referring type, stored on the
+    /// heap so that the type has a finite size
+    Boxed(Box<LinkedNode>),
+    /// A type behind a CBOR tag
+    Tagged(LinkedTagged),
+    /// A reference to a rule that names a primative, range, array or tagged type
+    Alias(LinkedAlias),
 }
 
 /// A Enum Variant of a node, so we provide helper convert to the enum
@@ -92,30 +236,474 @@ impl From<ConstrainedType> for LinkedNode {
     }
 }
 
-/// Main (only) entry function to this module
-pub(crate) fn link_node(node: &Node, ctx: &BTreeMap<String, Node>) -> FlattenResult<LinkedNode> {
+/// The rules a node may refer to, and the rules we are in the middle of linking so that a rule
+/// which refers back to itself is noticed
+pub(crate) struct Context<'a> {
+    rules: &'a BTreeMap<String, Node>,
+    /// The rules of the CDDL, which are rendered. A reference to a rule of the prelude is
+    /// replaced by it's type, as the prelude is not rendered
+    local: &'a BTreeMap<String, Node>,
+    /// Every rule being linked, and if it was referred to through an optional member or a vector
+    /// that may be empty
+    path: RefCell<Vec<(String, bool)>>,
+    /// The shallowest position of the path that a rule deeper in the path refers back to
+    back: Cell<usize>,
+}
+
+impl<'a> Context<'a> {
+    pub(crate) fn new(
+        rules: &'a BTreeMap<String, Node>,
+        local: &'a BTreeMap<String, Node>,
+    ) -> Context<'a> {
+        Context {
+            rules,
+            local,
+            path: RefCell::new(Vec::new()),
+            back: Cell::new(usize::MAX),
+        }
+    }
+
+    fn get(&self, key: &str) -> Option<&'a Node> {
+        self.rules.get(key)
+    }
+
+    /// Link a rule while it is on the path, returning the shallowest position of the path that
+    /// the rule (or a rule it refers to) refers back to
+    fn enter<T, F>(&self, key: &str, indirect: bool, f: F) -> FlattenResult<(T, usize)>
+    where
+        F: FnOnce(&Self) -> FlattenResult<T>,
+    {
+        let outer = self.back.replace(usize::MAX);
+        self.path.borrow_mut().push((key.into(), indirect));
+        let result = f(self);
+        self.path.borrow_mut().pop();
+        let back = self.back.replace(outer);
+        self.back.set(std::cmp::min(outer, back));
+        result.map(|node| (node, back))
+    }
+
+    fn position(&self, key: &str) -> Option<usize> {
+        self.path.borrow().iter().position(|(k, _)| k == key)
+    }
+
+    /// A reference back to the rule at a position of the path is a cycle. The cycle may be stored
+    /// when one of it's references may be absent, else the type would be infinite
+    fn refer_back(&self, n: usize, key: &str, indirect: bool) -> FlattenResult<()> {
+        let path = self.path.borrow();
+        if !indirect && !path[n + 1..].iter().any(|(_, indirect)| *indirect) {
+            return Err(self.cycle(n, key));
+        }
+        self.back.set(std::cmp::min(self.back.get(), n));
+        Ok(())
+    }
+
+    fn cycle(&self, n: usize, key: &str) -> ValidateError {
+        let path = self.path.borrow();
+        let keys = path[n..].iter().map(|(k, _)| k.clone());
+        ValidateError::Cycle(keys.chain(std::iter::once(key.to_string())).collect())
+    }
+}
+
+/// Link a rule of the CDDL, where the rules it refers to are resolved from the context
+pub(crate) fn link_rule(key: &str, node: &Node, ctx: &Context) -> FlattenResult<LinkedNode> {
+    ctx.enter(key, false, |ctx| link_node(node, ctx))
+        .map(|(node, _)| node)
+}
+
+/// An inline map is rendered as a struct of it's own, so every map nested inside a rule is
+/// hoisted into a rule named after the rule and the member that holds it (IE: the member net of
+/// thing is hoisted into thing-net)
+pub(crate) fn hoist(
+    rules: BTreeMap<String, LinkedNode>,
+) -> FlattenResult<BTreeMap<String, LinkedNode>> {
+    let mut hoisted = BTreeMap::new();
+    let rules = rules
+        .into_iter()
+        .map(|(key, node)| {
+            let node = match node {
+                LinkedNode::Struct(f) => LinkedNode::Struct(hoist_fields(&key, f, &mut hoisted)?),
+                LinkedNode::Record(f) => LinkedNode::Record(hoist_fields(&key, f, &mut hoisted)?),
+                LinkedNode::Fields(f) => LinkedNode::Fields(hoist_fields(&key, f, &mut hoisted)?),
+                LinkedNode::Union(u) => LinkedNode::Union(hoist_union(&key, u, &mut hoisted)?),
+                node => node,
+            };
+            Ok((key, node))
+        })
+        .collect::<FlattenResult<BTreeMap<String, LinkedNode>>>()?;
+    match hoisted.keys().find(|key| rules.contains_key(*key)) {
+        Some(key) => Err(ValidateError::DuplicateRule(key.clone())),
+        None => Ok(rules.into_iter().chain(hoisted).collect()),
+    }
+}
+
+/// Hoist the map of a member, which may be optional, and refer to it by name
+fn hoist_node(
+    name: &str,
+    node: LinkedNode,
+    hoisted: &mut BTreeMap<String, LinkedNode>,
+) -> FlattenResult<LinkedNode> {
+    let (node, foreign) = match node {
+        LinkedNode::Optional(node) => {
+            return hoist_node(name, *node, hoisted).map(|n| LinkedNode::Optional(Box::new(n)))
+        }
+        LinkedNode::Struct(f) => (
+            LinkedNode::Struct(hoist_fields(name, f, hoisted)?),
+            LinkedNode::ForeignStruct(name.into()),
+        ),
+        LinkedNode::Record(f) => (
+            LinkedNode::Record(hoist_fields(name, f, hoisted)?),
+            LinkedNode::ForeignStruct(name.into()),
+        ),
+        LinkedNode::Fields(f) => (
+            LinkedNode::Fields(hoist_fields(name, f, hoisted)?),
+            LinkedNode::ForeignStruct(name.into()),
+        ),
+        LinkedNode::Union(u) => (
+            LinkedNode::Union(hoist_union(name, u, hoisted)?),
+            LinkedNode::ForeignChoice(name.into()),
+        ),
+        node @ LinkedNode::Dictionary(_)
+        | node @ LinkedNode::Bits(_)
+        | node @ LinkedNode::Regexp(_)
+        | node @ LinkedNode::Embedded(_) => (node, LinkedNode::ForeignStruct(name.into())),
+        node => return Ok(node),
+    };
+    // Two members may be named alike (IE: the member b-c and the member c of b)
+    match hoisted.insert(name.to_string(), node) {
+        Some(_) => Err(ValidateError::DuplicateRule(name.to_string())),
+        None => Ok(foreign),
+    }
+}
+
+fn hoist_fields(
+    name: &str,
+    fields: Fields,
+    hoisted: &mut BTreeMap<String, LinkedNode>,
+) -> FlattenResult<Fields> {
+    let members = fields
+        .members
+        .into_iter()
+        .map(|LinkedKeyVal(key, node, wire)| {
+            hoist_node(&format!("{}-{}", name, key), node, hoisted)
+                .map(|node| LinkedKeyVal(key, node, wire))
+        })
+        .collect::<FlattenResult<Vec<LinkedKeyVal>>>()?;
+    Ok(Fields { members })
+}
+
+/// The members of a variant are named after the struct the variant is rendered as
+fn hoist_union(
+    name: &str,
+    union: LinkedUnion,
+    hoisted: &mut BTreeMap<String, LinkedNode>,
+) -> FlattenResult<LinkedUnion> {
+    let variants = union
+        .variants
+        .into_iter()
+        .map(|LinkedKeyVal(key, node, wire)| match node {
+            LinkedNode::Fields(f) => hoist_fields(&format!("{}-{}", name, key), f, hoisted)
+                .map(|f| LinkedKeyVal(key, LinkedNode::Fields(f), wire)),
+            node => Ok(LinkedKeyVal(key, node, wire)),
+        })
+        .collect::<FlattenResult<Vec<LinkedKeyVal>>>()?;
+    Ok(LinkedUnion {
+        discriminant: union.discriminant,
+        variants,
+    })
+}
+
+fn link_node(node: &Node, ctx: &Context) -> FlattenResult<LinkedNode> {
     match node {
         Node::Literal(lit) => Ok(LinkedNode::Literal(lit.clone())),
+        // A byte string of N size is stored like an array of N bytes, which is a byte string
+        Node::ConstrainedType(ConstrainedType::Bytes(len)) => Ok(LinkedNode::Array(LinkedArray {
+            ty: Box::new(LinkedNode::ConstrainedType(ConstrainedType::U8)),
+            len: *len as usize,
+        })),
         Node::ConstrainedType(t) => Ok(LinkedNode::ConstrainedType(t.clone())),
-        Node::Foreign(t) => link_foreign(t, ctx),
+        Node::Foreign(t) => link_foreign(t, ctx, false),
         Node::Group(g) => link_group(g, ctx),
         Node::Map(g) => link_struct(g, ctx),
+        Node::Record(g) => link_record(g, ctx),
         Node::Array(a) => link_array(a, ctx),
-        _ => unimplemented!(),
+        Node::Vector(v) => link_vector(v, ctx),
+        Node::Dictionary(d) => link_dictionary(d, ctx),
+        Node::Choice(c) => link_choice(c, ctx),
+        Node::Range(r) => link_range(r, ctx),
+        Node::Optional(o) => link_optional(o, ctx),
+        Node::Tagged(t) => link_tagged(t, ctx),
+        Node::Control(c) => link_control(c, ctx),
+        // A key: value is only linked as the member of a group
+        Node::KeyVal(_) => Err(ValidateError::InvalidType),
+    }
+}
+
+fn link_optional(node: &Node, ctx: &Context) -> FlattenResult<LinkedNode> {
+    // An optional member may refer back to the struct that holds it
+    let linked = match node {
+        Node::Foreign(key) => link_foreign(key, ctx, true),
+        node => link_node(node, ctx),
+    };
+    linked.and_then(|node| match node {
+        // A literal is always present, so it cannot be optional
+        LinkedNode::Literal(_) => Err(ValidateError::InvalidLiteral),
+        // We don't accept inline choices, they must be named by a rule
+        LinkedNode::Choice(_) => Err(ValidateError::InvalidChoice),
+        node => Ok(LinkedNode::Optional(Box::new(node))),
+    })
+}
+
+fn link_tagged(tagged: &Tagged, ctx: &Context) -> FlattenResult<LinkedNode> {
+    link_node(&tagged.ty, ctx).and_then(|node| match unaliased(&node) {
+        // An encoded value writes it's own tag (IE: #6.24(bstr .cbor inner))
+        LinkedNode::Embedded(embedded) if embedded.tag.is_none() => {
+            Ok(LinkedNode::Embedded(LinkedEmbedded {
+                tag: Some(tagged.tag),
+                ..embedded.clone()
+            }))
+        }
+        // Primatives, fixed size arrays, tagged types and types defined externally are the only
+        // acceptable tagged types. The bounds of a range are not checked behind a tag
+        LinkedNode::ConstrainedType(_)
+        | LinkedNode::Array(_)
+        | LinkedNode::Tagged(_)
+        | LinkedNode::ForeignStruct(_)
+        | LinkedNode::ForeignChoice(_) => Ok(LinkedNode::Tagged(LinkedTagged {
+            tag: tagged.tag,
+            ty: Box::new(node),
+        })),
+        _ => Err(ValidateError::InvalidTag),
+    })
+}
+
+fn link_range(range: &Range, ctx: &Context) -> FlattenResult<LinkedNode> {
+    let bound = |node: &Node| match link_node(node, ctx)? {
+        LinkedNode::Literal(lit) => lit.as_int().ok_or(ValidateError::InvalidRange),
+        _ => Err(ValidateError::InvalidRange),
+    };
+    let min = bound(&range.start)?;
+    let max = match range.inclusive {
+        true => bound(&range.end)?,
+        false => bound(&range.end)? - 1,
+    };
+    if min <= max {
+        Ok(LinkedNode::Range(LinkedRange {
+            ty: ConstrainedType::fit(min, max),
+            min: Literal::from_int(min),
+            max: Literal::from_int(max),
+            ne: None,
+        }))
+    } else {
+        Err(ValidateError::InvalidRange)
+    }
+}
+
+fn link_control(ctrl: &Control, ctx: &Context) -> FlattenResult<LinkedNode> {
+    let ty = link_node(&ctrl.ty, ctx)?;
+    match ctrl.op {
+        ControlOp::Bits => link_bits(ty, &ctrl.arg, ctx),
+        ControlOp::Default => link_default(ty, link_node(&ctrl.arg, ctx)?),
+        ControlOp::Regexp => link_regexp(ty, link_node(&ctrl.arg, ctx)?),
+        ControlOp::Cbor => link_embedded(ty, &ctrl.arg, false, ctx),
+        ControlOp::CborSeq => link_embedded(ty, &ctrl.arg, true, ctx),
+        op => link_comparison(ty, op, link_node(&ctrl.arg, ctx)?),
     }
 }
 
-fn link_array(arr: &Array, ctx: &BTreeMap<String, Node>) -> FlattenResult<LinkedNode> {
+/// The flags are named by a choice of bit positions, which may be written inline or named by a
+/// rule. A position must be within the integer
+fn link_bits(ty: LinkedNode, arg: &Node, ctx: &Context) -> FlattenResult<LinkedNode> {
+    let ty = match unaliased(&ty) {
+        LinkedNode::ConstrainedType(ConstrainedType::UInt) => ConstrainedType::U64,
+        LinkedNode::ConstrainedType(
+            ty @ (ConstrainedType::U8
+            | ConstrainedType::U16
+            | ConstrainedType::U32
+            | ConstrainedType::U64),
+        ) => ty.clone(),
+        _ => return Err(ValidateError::InvalidControl),
+    };
+    let arg = match arg {
+        Node::Foreign(key) => ctx
+            .get(key)
+            .ok_or_else(|| ValidateError::ForeignKey(key.into()))?,
+        arg => arg,
+    };
+    let width = ty
+        .bounds()
+        .map_or(0, |(_, max)| 128 - max.leading_zeros() as u64);
+    let flags = match link_node(arg, ctx)? {
+        LinkedNode::Choice(LinkedChoice { variants }) => variants
+            .into_iter()
+            .map(|LinkedKeyVal(name, node, ..)| match unaliased(&node) {
+                LinkedNode::Literal(Literal::UInt(bit)) if *bit < width => Ok((name, *bit)),
+                _ => Err(ValidateError::InvalidControlArg),
+            })
+            .collect::<FlattenResult<Vec<(String, u64)>>>()?,
+        _ => return Err(ValidateError::InvalidControlArg),
+    };
+    Ok(LinkedNode::Bits(LinkedBits { ty, flags }))
+}
+
+/// A value by default must be a value of the type, a member with a default is stored as the type
+/// an alias names
+fn link_default(ty: LinkedNode, arg: LinkedNode) -> FlattenResult<LinkedNode> {
+    let value = match arg {
+        LinkedNode::Literal(lit) => lit,
+        _ => return Err(ValidateError::InvalidControlArg),
+    };
+    let ty = unaliased(&ty).clone();
+    let valid = match (&ty, &value) {
+        (LinkedNode::Range(range), lit) => lit.as_int().is_some_and(|val| {
+            range_bounds(range).contains(&val) && range.ne.as_ref() != Some(lit)
+        }),
+        (LinkedNode::ConstrainedType(ConstrainedType::Bool), Literal::Bool(_)) => true,
+        (LinkedNode::ConstrainedType(ConstrainedType::Str(n)), Literal::Str(s)) => {
+            s.len() as u64 <= *n
+        }
+        (LinkedNode::ConstrainedType(ConstrainedType::TStr), Literal::Str(_)) => true,
+        (LinkedNode::ConstrainedType(ty), lit) => match (ty.bounds(), lit.as_int()) {
+            (Some((min, max)), Some(val)) => (min..=max).contains(&val),
+            _ => false,
+        },
+        _ => false,
+    };
+    match valid {
+        true => Ok(LinkedNode::Default(LinkedDefault {
+            ty: Box::new(ty),
+            value,
+        })),
+        false => Err(ValidateError::InvalidControlArg),
+    }
+}
+
+fn link_regexp(ty: LinkedNode, arg: LinkedNode) -> FlattenResult<LinkedNode> {
+    let ty = match unaliased(&ty) {
+        LinkedNode::ConstrainedType(ty @ (ConstrainedType::Str(_) | ConstrainedType::TStr)) => {
+            ty.clone()
+        }
+        _ => return Err(ValidateError::InvalidControl),
+    };
+    match arg {
+        LinkedNode::Literal(Literal::Str(pattern)) => {
+            regex::Regex::new(&pattern).map_err(|_| ValidateError::InvalidControlArg)?;
+            Ok(LinkedNode::Regexp(LinkedRegexp { ty, pattern }))
+        }
+        _ => Err(ValidateError::InvalidControlArg),
+    }
+}
+
+/// The encoded type is referred to by name, and the bytes break a cycle of references as the
+/// value is not stored. A byte string of N size holds at most N bytes
+fn link_embedded(
+    ty: LinkedNode,
+    arg: &Node,
+    seq: bool,
+    ctx: &Context,
+) -> FlattenResult<LinkedNode> {
+    let max = match unaliased(&ty) {
+        LinkedNode::ConstrainedType(ConstrainedType::BStr) => usize::MAX,
+        LinkedNode::Array(LinkedArray { ty, len }) if **ty == ConstrainedType::U8.into() => *len,
+        _ => return Err(ValidateError::InvalidControl),
+    };
+    let ty = match arg {
+        Node::Foreign(key) => {
+            let back = ctx.back.get();
+            let node = link_foreign(key, ctx, true);
+            ctx.back.set(back);
+            match node? {
+                LinkedNode::Boxed(node) => *node,
+                node => node,
+            }
+        }
+        _ => return Err(ValidateError::InvalidControlArg),
+    };
+    match unaliased(&ty) {
+        LinkedNode::ForeignStruct(_) | LinkedNode::ForeignChoice(_) => {
+            Ok(LinkedNode::Embedded(LinkedEmbedded {
+                ty: Box::new(ty),
+                max,
+                seq,
+                tag: None,
+            }))
+        }
+        _ => Err(ValidateError::InvalidControlArg),
+    }
+}
+
+/// A comparison narrows the bounds of an integer type (or a range), except .ne which excludes a
+/// single value. The type keeps it's width
+fn link_comparison(ty: LinkedNode, op: ControlOp, arg: LinkedNode) -> FlattenResult<LinkedNode> {
+    let val = match arg {
+        LinkedNode::Literal(lit) => lit.as_int().ok_or(ValidateError::InvalidControlArg)?,
+        _ => return Err(ValidateError::InvalidControlArg),
+    };
+    let (ty, (min, max), ne) = match unaliased(&ty) {
+        LinkedNode::Range(range) => {
+            let bounds = range_bounds(range);
+            let bounds = (*bounds.start(), *bounds.end());
+            (range.ty.clone(), bounds, range.ne.clone())
+        }
+        LinkedNode::ConstrainedType(ty) => match ty.bounds() {
+            Some(bounds) => (ty.clone(), bounds, None),
+            None => return Err(ValidateError::InvalidControl),
+        },
+        _ => return Err(ValidateError::InvalidControl),
+    };
+    let (min, max, ne) = match op {
+        ControlOp::Lt => (min, std::cmp::min(max, val - 1), ne),
+        ControlOp::Le => (min, std::cmp::min(max, val), ne),
+        ControlOp::Gt => (std::cmp::max(min, val + 1), max, ne),
+        ControlOp::Ge => (std::cmp::max(min, val), max, ne),
+        ControlOp::Eq => (std::cmp::max(min, val), std::cmp::min(max, val), ne),
+        ControlOp::Ne if ne.is_some() => return Err(ValidateError::InvalidControl),
+        _ => (min, max, Some(Literal::from_int(val))),
+    };
+    // The range must hold a value other than the excluded value
+    let ne_ok = ne
+        .as_ref()
+        .and_then(Literal::as_int)
+        .is_none_or(|ne| min != max || min != ne);
+    if min <= max && ne_ok {
+        Ok(LinkedNode::Range(LinkedRange {
+            ty,
+            min: Literal::from_int(min),
+            max: Literal::from_int(max),
+            ne,
+        }))
+    } else {
+        Err(ValidateError::InvalidRange)
+    }
+}
+
+fn range_bounds(range: &LinkedRange) -> std::ops::RangeInclusive<i128> {
+    match (range.min.as_int(), range.max.as_int()) {
+        (Some(min), Some(max)) => min..=max,
+        // A range without integer bounds holds no values
+        _ => std::ops::RangeInclusive::new(1, 0),
+    }
+}
+
+fn link_array(arr: &Array, ctx: &Context) -> FlattenResult<LinkedNode> {
     // Similar to link_foreign, we only accept certain types as an array, and we don't follow
     // nesting types so we can flatten them
     link_node(&arr.ty, ctx).and_then(|node| match node {
-        // We don't accept nested arrays
-        LinkedNode::Array(..) => Err(ValidateError::InvalidArray),
+        // We accept nested arrays of a fixed size only
+        LinkedNode::Vector(..) => Err(ValidateError::InvalidArray),
         // We don't accept inline fields inside an array
         LinkedNode::Fields(_) => Err(ValidateError::InvalidArray),
         // We don't accept inline structs defined inside an array
-        LinkedNode::Struct(_) => Err(ValidateError::InvalidArray),
-        // ConstainedType or Struct defined externally are the only acceptable array types
+        LinkedNode::Struct(_)
+        | LinkedNode::Record(_)
+        | LinkedNode::Dictionary(_)
+        | LinkedNode::Bits(_)
+        | LinkedNode::Regexp(_)
+        | LinkedNode::Embedded(_) => Err(ValidateError::InvalidArray),
+        // We don't accept inline choices defined inside an array
+        LinkedNode::Choice(_) | LinkedNode::Union(_) => Err(ValidateError::InvalidArray),
+        // Every entry of an array is stored
+        LinkedNode::Literal(_) | LinkedNode::Optional(_) => Err(ValidateError::InvalidArray),
+        // ConstainedType, Array or Struct defined externally are the only acceptable array types
         n => Ok(LinkedNode::Array(LinkedArray {
             ty: Box::new(n),
             len: arr.len,
@@ -123,58 +711,370 @@ fn link_array(arr: &Array, ctx: &BTreeMap<String, Node>) -> FlattenResult<Linked
     })
 }
 
-fn link_foreign(key: &str, ctx: &BTreeMap<String, Node>) -> FlattenResult<LinkedNode> {
-    // When linking a "foreign" struct, we simply note it's remote name instead of
-    // following the struct deeper.
-    ctx.get(key)
-        .ok_or_else(|| ValidateError::ForeignKey(key.into()))
-        .and_then(|node| link_node(node, ctx))
-        .map(|node| match node {
-            LinkedNode::Struct(_s) => LinkedNode::ForeignStruct(key.into()),
-            node => node,
-        })
+fn link_vector(vec: &Vector, ctx: &Context) -> FlattenResult<LinkedNode> {
+    // A vector must be bounded so that it can be stored without allocating
+    if vec.max == usize::MAX {
+        return Err(ValidateError::InvalidArraySize);
+    }
+    // A vector that may be empty may refer back to the struct that holds it
+    let linked = match vec.ty.as_ref() {
+        Node::Foreign(key) => link_foreign(key, ctx, vec.min == 0),
+        node => link_node(node, ctx),
+    };
+    linked.and_then(|node| match unaliased(&node) {
+        // Primatives, ranges and types defined externally are the only acceptable vector types
+        LinkedNode::ConstrainedType(ConstrainedType::Str(_))
+        | LinkedNode::ConstrainedType(ConstrainedType::Bytes(_))
+        | LinkedNode::ConstrainedType(ConstrainedType::BStr)
+        | LinkedNode::ConstrainedType(ConstrainedType::F16) => Err(ValidateError::InvalidArray),
+        LinkedNode::ConstrainedType(_)
+        | LinkedNode::Range(_)
+        | LinkedNode::ForeignStruct(_)
+        | LinkedNode::ForeignChoice(_)
+        | LinkedNode::Boxed(_) => Ok(LinkedNode::Vector(LinkedVector {
+            min: vec.min,
+            max: vec.max,
+            ty: Box::new(node),
+        })),
+        _ => Err(ValidateError::InvalidArray),
+    })
+}
+
+/// When linking a "foreign" struct, we simply note it's remote name instead of following the
+/// struct deeper. A reference that may be absent (an optional member or a vector that may be
+/// empty) may refer back to a rule we are linking, and is boxed when it is part of the cycle
+fn link_foreign(key: &str, ctx: &Context, indirect: bool) -> FlattenResult<LinkedNode> {
+    let node = ctx
+        .get(key)
+        .ok_or_else(|| ValidateError::ForeignKey(key.into()))?;
+    let depth = ctx.path.borrow().len();
+    let (node, back) = match ctx.position(key) {
+        Some(n) => ctx
+            .refer_back(n, key, indirect)
+            .and_then(|_| foreign_kind(key, node).ok_or_else(|| ctx.cycle(n, key)))
+            .map(|node| (node, n))?,
+        None => ctx
+            .enter(key, indirect, |ctx| link_node(node, ctx))
+            .map(|(node, back)| (reference(key, node, ctx), back))?,
+    };
+    match (node, indirect && back < depth) {
+        (node @ LinkedNode::ForeignStruct(_), true)
+        | (node @ LinkedNode::ForeignChoice(_), true) => Ok(LinkedNode::Boxed(Box::new(node))),
+        (_, true) => Err(ctx.cycle(back, key)),
+        (node, false) => Ok(node),
+    }
+}
+
+/// The reference to a linked rule. Structs and choices are referred to by name, as are the
+/// other types a rule of the CDDL may name
+fn reference(key: &str, node: LinkedNode, ctx: &Context) -> LinkedNode {
+    match node {
+        LinkedNode::Struct(_s) => LinkedNode::ForeignStruct(key.into()),
+        LinkedNode::Record(_r) => LinkedNode::ForeignStruct(key.into()),
+        LinkedNode::Fields(_f) => LinkedNode::ForeignStruct(key.into()),
+        LinkedNode::Choice(_c) => LinkedNode::ForeignChoice(key.into()),
+        LinkedNode::Union(_u) => LinkedNode::ForeignChoice(key.into()),
+        LinkedNode::Dictionary(_d) => LinkedNode::ForeignStruct(key.into()),
+        LinkedNode::Bits(_b) => LinkedNode::ForeignStruct(key.into()),
+        LinkedNode::Regexp(_r) => LinkedNode::ForeignStruct(key.into()),
+        LinkedNode::Embedded(_e) => LinkedNode::ForeignStruct(key.into()),
+        node if ctx.local.contains_key(key) && aliased(&node) => LinkedNode::Alias(LinkedAlias {
+            name: key.into(),
+            ty: Box::new(node),
+        }),
+        node => node,
+    }
+}
+
+/// A rule of these types is referred to by it's name
+fn aliased(node: &LinkedNode) -> bool {
+    matches!(
+        node,
+        LinkedNode::ConstrainedType(_)
+            | LinkedNode::Range(_)
+            | LinkedNode::Array(_)
+            | LinkedNode::Vector(_)
+            | LinkedNode::Tagged(_)
+            | LinkedNode::Alias(_)
+    )
+}
+
+/// The type named by an alias. A value by default does not change the type
+pub(crate) fn unaliased(node: &LinkedNode) -> &LinkedNode {
+    match node {
+        LinkedNode::Alias(LinkedAlias { ty, .. }) => unaliased(ty),
+        LinkedNode::Default(LinkedDefault { ty, .. }) => unaliased(ty),
+        node => node,
+    }
+}
+
+/// The reference to a rule that is not linked yet, which must be a type we can name
+fn foreign_kind(key: &str, node: &Node) -> Option<LinkedNode> {
+    match node {
+        Node::Map(g) if matches!(g.members.as_slice(), [Node::Choice(_)]) => {
+            Some(LinkedNode::ForeignChoice(key.into()))
+        }
+        Node::Map(_) | Node::Record(_) | Node::Dictionary(_) => {
+            Some(LinkedNode::ForeignStruct(key.into()))
+        }
+        Node::Control(Control {
+            op: ControlOp::Bits | ControlOp::Regexp | ControlOp::Cbor | ControlOp::CborSeq,
+            ..
+        }) => Some(LinkedNode::ForeignStruct(key.into())),
+        Node::Choice(_) => Some(LinkedNode::ForeignChoice(key.into())),
+        _ => None,
+    }
+}
+
+fn link_dictionary(dict: &Dictionary, ctx: &Context) -> FlattenResult<LinkedNode> {
+    let key = link_node(&dict.key, ctx)?;
+    // A key is an integer or text
+    let key = match unaliased(&key) {
+        key @ LinkedNode::ConstrainedType(ty) => match ty {
+            ConstrainedType::Bool
+            | ConstrainedType::F16
+            | ConstrainedType::F32
+            | ConstrainedType::F64
+            | ConstrainedType::Bytes(_)
+            | ConstrainedType::BStr
+            | ConstrainedType::Any => return Err(ValidateError::InvalidDictionary),
+            _ => key.clone(),
+        },
+        _ => return Err(ValidateError::InvalidDictionary),
+    };
+    // A dictionary that may be empty may refer back to the struct that holds it
+    let val = match dict.val.as_ref() {
+        Node::Foreign(key) => link_foreign(key, ctx, dict.min == 0),
+        node => link_node(node, ctx),
+    }?;
+    match unaliased(&val) {
+        // Every entry is stored, and inline types must be named by a rule
+        LinkedNode::Literal(_)
+        | LinkedNode::Optional(_)
+        | LinkedNode::Vector(_)
+        | LinkedNode::Fields(_)
+        | LinkedNode::Struct(_)
+        | LinkedNode::Record(_)
+        | LinkedNode::Dictionary(_)
+        | LinkedNode::Bits(_)
+        | LinkedNode::Regexp(_)
+        | LinkedNode::Embedded(_)
+        | LinkedNode::Choice(_)
+        | LinkedNode::Union(_) => Err(ValidateError::InvalidDictionary),
+        _ => Ok(LinkedNode::Dictionary(LinkedDictionary {
+            min: dict.min,
+            max: dict.max,
+            key: Box::new(key),
+            val: Box::new(val),
+        })),
+    }
+}
+
+fn link_choice(choice: &Choice, ctx: &Context) -> FlattenResult<LinkedNode> {
+    let variants = choice
+        .variants
+        .iter()
+        .enumerate()
+        .map(|(n, node)| link_variant(n, node, ctx))
+        .collect::<FlattenResult<Vec<LinkedKeyVal>>>()?;
+    // Every variant is rendered as an enum member, so the names must be unique
+    let names_ok = unique_names(variants.iter().map(|v| v.0.as_str()));
+    // A choice of literals is an enum, every literal must be of the same kind and unique
+    let kinds = variants
+        .iter()
+        .map(|v| literal_kind(&v.1))
+        .collect::<Vec<Option<&str>>>();
+    let kinds_ok = match kinds[0] {
+        Some("int") | Some("str") | None => kinds.iter().all(|kind| *kind == kinds[0]),
+        _ => false,
+    };
+    let literals_ok = unique(
+        variants
+            .iter()
+            .map(|v| &v.1)
+            .filter(|node| matches!(node, LinkedNode::Literal(_))),
+    );
+    if names_ok && kinds_ok && literals_ok {
+        Ok(LinkedNode::Choice(LinkedChoice { variants }))
+    } else {
+        Err(ValidateError::InvalidChoice)
+    }
+}
+
+fn unique<T: PartialEq, I: Iterator<Item = T>>(iter: I) -> bool {
+    let items = iter.collect::<Vec<T>>();
+    items
+        .iter()
+        .enumerate()
+        .all(|(n, item)| !items[..n].contains(item))
+}
+
+/// Names are rendered in the case of each language, so they must remain unique once cased
+fn unique_names<'a, I: Iterator<Item = &'a str> + Clone>(names: I) -> bool {
+    unique(names.clone().map(|name| name.to_upper_camel_case()))
+        && unique(names.map(|name| name.to_snake_case()))
+}
+
+fn literal_kind(node: &LinkedNode) -> Option<&'static str> {
+    match node {
+        LinkedNode::Literal(Literal::Int(_)) | LinkedNode::Literal(Literal::UInt(_)) => Some("int"),
+        LinkedNode::Literal(Literal::Str(_)) => Some("str"),
+        LinkedNode::Literal(_) => Some("other"),
+        _ => None,
+    }
 }
 
-fn link_group(map: &Group, ctx: &BTreeMap<String, Node>) -> FlattenResult<LinkedNode> {
-    link_field_key_values(map, ctx).map(|members| LinkedNode::Fields(Fields { members }))
+/// A variant referencing another rule takes the rule name. Anonymous literals are named after
+/// their value, and other anonymous variants are numbered
+fn link_variant(n: usize, node: &Node, ctx: &Context) -> FlattenResult<LinkedKeyVal> {
+    // A choice made from a group is named by the keys of the group
+    if let Node::KeyVal(KeyVal(key, node, _)) = node {
+        return link_variant(n, node, ctx)
+            .map(|LinkedKeyVal(_, node, wire)| LinkedKeyVal(key.clone(), node, wire));
+    }
+    let name = match node {
+        Node::Foreign(key) => key.clone(),
+        Node::Literal(Literal::Str(s)) if s.starts_with(char::is_alphabetic) => s.clone(),
+        Node::Literal(Literal::UInt(v)) => format!("value-{}", v),
+        Node::Literal(Literal::Int(v)) => format!("value-neg-{}", v.unsigned_abs()),
+        _ => format!("variant-{}", n),
+    };
+    link_node(node, ctx).and_then(|node| match unaliased(&node) {
+        // A variant must be a type we can name, so we don't accept inline groups or structs
+        LinkedNode::Fields(_)
+        | LinkedNode::Struct(_)
+        | LinkedNode::Record(_)
+        | LinkedNode::Dictionary(_)
+        | LinkedNode::Bits(_)
+        | LinkedNode::Regexp(_)
+        | LinkedNode::Embedded(_)
+        | LinkedNode::Choice(_)
+        | LinkedNode::Union(_)
+        | LinkedNode::Vector(_)
+        | LinkedNode::Tagged(_) => Err(ValidateError::InvalidChoice),
+        _ => Ok(LinkedKeyVal::new(name, node)),
+    })
+}
+
+fn link_group(map: &Group, ctx: &Context) -> FlattenResult<LinkedNode> {
+    link_fields(map, ctx).map(|members| LinkedNode::Fields(Fields { members }))
+}
+
+fn link_struct(map: &Group, ctx: &Context) -> FlattenResult<LinkedNode> {
+    match map.members.as_slice() {
+        // A map made of a group choice is a union of structs
+        [Node::Choice(choice)] => link_union(choice, ctx),
+        _ => link_fields(map, ctx).map(|members| LinkedNode::Struct(Fields { members })),
+    }
 }
 
-fn link_struct(map: &Group, ctx: &BTreeMap<String, Node>) -> FlattenResult<LinkedNode> {
-    link_field_key_values(map, ctx).map(|members| LinkedNode::Struct(Fields { members }))
+/// Every entry of a record is a field, so an entry may not refer to a group
+fn link_record(record: &Group, ctx: &Context) -> FlattenResult<LinkedNode> {
+    let members = link_fields(record, ctx)?;
+    match members
+        .iter()
+        .any(|LinkedKeyVal(_, node, _)| matches!(node, LinkedNode::Fields(_)))
+    {
+        true => Err(ValidateError::InvalidArray),
+        false => Ok(LinkedNode::Record(Fields { members })),
+    }
 }
 
-fn link_field_key_values(
-    map: &Group,
-    ctx: &BTreeMap<String, Node>,
-) -> FlattenResult<Vec<LinkedKeyVal>> {
-    Ok(link_fields(map, ctx)?
+fn link_union(choice: &Choice, ctx: &Context) -> FlattenResult<LinkedNode> {
+    let groups = choice
+        .variants
+        .iter()
+        .map(|node| match node {
+            Node::Group(group) => link_fields(group, ctx).map(|fields| (group, fields)),
+            _ => Err(ValidateError::InvalidChoice),
+        })
+        .collect::<FlattenResult<Vec<(&Group, Vec<LinkedKeyVal>)>>>()?;
+    let discriminant = find_discriminant(&groups).ok_or(ValidateError::InvalidChoice)?;
+    let variants = groups
         .into_iter()
-        .map(LinkedKeyVal::from)
-        .collect())
+        .map(|(group, members)| {
+            let name = discriminant_name(&discriminant, group, &members);
+            LinkedKeyVal::new(name, LinkedNode::Fields(Fields { members }))
+        })
+        .collect::<Vec<LinkedKeyVal>>();
+    if unique_names(variants.iter().map(|v| v.0.as_str())) {
+        Ok(LinkedNode::Union(LinkedUnion {
+            discriminant,
+            variants,
+        }))
+    } else {
+        Err(ValidateError::InvalidChoice)
+    }
+}
+
+/// The discriminant is the first literal member that every variant declares at the same
+/// position, with a literal of the same kind and a value unique to the variant
+fn find_discriminant(groups: &[(&Group, Vec<LinkedKeyVal>)]) -> Option<String> {
+    let (_, first) = groups.first()?;
+    first
+        .iter()
+        .enumerate()
+        .filter(|(_, LinkedKeyVal(_, node, _))| matches!(literal_kind(node), Some("int" | "str")))
+        .find(|(n, LinkedKeyVal(key, node, wire))| {
+            let same = groups.iter().all(|(_, members)| match members.get(*n) {
+                Some(LinkedKeyVal(k, v, w)) => {
+                    k == key && w == wire && literal_kind(v) == literal_kind(node)
+                }
+                None => false,
+            });
+            same && unique(groups.iter().map(|(_, members)| &members[*n].1))
+        })
+        .map(|(_, LinkedKeyVal(key, ..))| key.clone())
+}
+
+/// A variant is named after the rule of it's discriminant, or it's discriminant value
+fn discriminant_name(key: &str, group: &Group, members: &[LinkedKeyVal]) -> String {
+    let foreign = group.members.iter().find_map(|node| match node {
+        Node::KeyVal(KeyVal(k, v, _)) if k == key => match v.as_ref() {
+            Node::Foreign(name) => Some(name.clone()),
+            _ => None,
+        },
+        _ => None,
+    });
+    let literal = members.iter().find_map(|LinkedKeyVal(k, v, _)| match v {
+        LinkedNode::Literal(lit) if k == key => Some(lit),
+        _ => None,
+    });
+    match (foreign, literal) {
+        (Some(name), _) => name,
+        (None, Some(Literal::Str(s))) if s.starts_with(char::is_alphabetic) => s.clone(),
+        (None, Some(Literal::UInt(v))) => format!("{}-{}", key, v),
+        (None, Some(Literal::Int(v))) => format!("{}-neg-{}", key, v.unsigned_abs()),
+        (_, _) => key.to_string(),
+    }
 }
 
-fn link_fields(
-    map: &Group,
-    ctx: &BTreeMap<String, Node>,
-) -> FlattenResult<Vec<(String, LinkedNode)>> {
+fn link_fields(map: &Group, ctx: &Context) -> FlattenResult<Vec<LinkedKeyVal>> {
     Ok(map
         .members
         .iter()
         .map(|node| link_field_member(node, ctx))
-        .collect::<FlattenResult<Vec<Vec<(String, LinkedNode)>>>>()?
+        .collect::<FlattenResult<Vec<Vec<LinkedKeyVal>>>>()?
         .into_iter()
         .flatten()
         .collect())
 }
 
-fn link_field_member(
-    node: &Node,
-    ctx: &BTreeMap<String, Node>,
-) -> FlattenResult<Vec<(String, LinkedNode)>> {
+fn link_field_member(node: &Node, ctx: &Context) -> FlattenResult<Vec<LinkedKeyVal>> {
     match node {
-        Node::KeyVal(KeyVal(k, v)) => link_node(v, ctx).map(|n| vec![(k.clone(), n)]),
+        Node::KeyVal(KeyVal(k, v, wire)) => link_node(v, ctx).and_then(|n| match n {
+            // We don't accept inline choices, they must be named by a rule
+            LinkedNode::Choice(_) => Err(ValidateError::InvalidChoice),
+            n => Ok(vec![LinkedKeyVal(k.clone(), n, wire.clone())]),
+        }),
         Node::Foreign(key) => match ctx.get(key) {
-            Some(Node::Group(g)) => link_fields(g, ctx),
+            // A group is pasted into the struct, so it may never refer back to it
+            Some(Node::Group(g)) => match ctx.position(key) {
+                Some(n) => Err(ctx.cycle(n, key)),
+                None => ctx
+                    .enter(key, false, |ctx| link_fields(g, ctx))
+                    .map(|(f, _)| f),
+            },
             //Some(Node::Map(g)) => link_struct(g, ctx).map(|n| vec![(key.clone(), n)]),
             _ => Err(ValidateError::InvalidType),
         },
@@ -1,15 +1,33 @@
-use minicbor_bindgen::{Language, Options};
+use minicbor_bindgen::{Alias, Language, Options};
 use std::fs;
 use std::path::PathBuf;
 
 fn render_cddl(path: &str, opts: Options) -> () {
+    render_cddl_file("tests/thing.cddl", path, opts)
+}
+
+fn render_cddl_file(file: &str, path: &str, opts: Options) -> () {
     let root = PathBuf::from(std::env!("CARGO_MANIFEST_DIR"));
-    let cddl = fs::read_to_string(root.join("tests/thing.cddl")).unwrap();
+    let cddl = fs::read_to_string(root.join(file)).unwrap();
     let mut lib = minicbor_bindgen::render_lib(&cddl, &opts).unwrap();
     lib.push_str(" fn main () {}");
     fs::write(root.join("tests").join(path), lib).unwrap();
 }
 
+/// Render a CDDL fixture of tests/run with the checks of the fixture appended as main, so the
+/// generated code is run and not only compiled
+fn render_run(name: &str, opts: Options) -> String {
+    let root = PathBuf::from(std::env!("CARGO_MANIFEST_DIR"));
+    let run = root.join("tests").join("run");
+    let cddl = fs::read_to_string(run.join(format!("{}.cddl", name))).unwrap();
+    let main = fs::read_to_string(run.join(format!("{}.rs", name))).unwrap();
+    let mut lib = minicbor_bindgen::render_lib(&cddl, &opts).unwrap();
+    lib.push_str(&main);
+    let path = format!("tests/__generated__/run_{}.rs", name);
+    fs::write(root.join(&path), lib).unwrap();
+    path
+}
+
 #[test]
 fn test_render_lib() {
     let runner = trybuild::TestCases::new();
@@ -60,10 +78,100 @@ fn test_render_lib() {
         },
     );
 
+    // Aliases may be rendered as newtypes instead
+    for (path, language) in [
+        ("__generated__/c_newtype.rs", Language::C),
+        ("__generated__/rust_newtype.rs", Language::Rust),
+        ("__generated__/typescript_newtype.rs", Language::Typescript),
+    ] {
+        render_cddl(
+            path,
+            Options {
+                language,
+                alias: Alias::Newtype,
+                ..Options::default()
+            },
+        );
+    }
+
     runner.pass("tests/__generated__/c.rs");
     runner.pass("tests/__generated__/c_with_prefix.rs");
     runner.pass("tests/__generated__/rust.rs");
     runner.pass("tests/__generated__/rust_with_prefix.rs");
     runner.pass("tests/__generated__/typescript.rs");
     runner.pass("tests/__generated__/typescript_with_prefix.rs");
+    runner.pass("tests/__generated__/c_newtype.rs");
+    runner.pass("tests/__generated__/rust_newtype.rs");
+    runner.pass("tests/__generated__/typescript_newtype.rs");
+}
+
+#[test]
+fn test_render_unconstrained() {
+    let runner = trybuild::TestCases::new();
+    render_cddl_file(
+        "tests/owned.cddl",
+        "__generated__/owned_rust.rs",
+        Options {
+            language: Language::Rust,
+            ..Options::default()
+        },
+    );
+    render_cddl_file(
+        "tests/owned.cddl",
+        "__generated__/owned_typescript.rs",
+        Options {
+            language: Language::Typescript,
+            ..Options::default()
+        },
+    );
+
+    // C can not store unconstrained types
+    let root = PathBuf::from(std::env!("CARGO_MANIFEST_DIR"));
+    let cddl = fs::read_to_string(root.join("tests/owned.cddl")).unwrap();
+    assert!(minicbor_bindgen::render_lib(&cddl, &Options::default()).is_err());
+
+    runner.pass("tests/__generated__/owned_rust.rs");
+    runner.pass("tests/__generated__/owned_typescript.rs");
+}
+
+#[test]
+fn test_run() {
+    let runner = trybuild::TestCases::new();
+    for name in [
+        "ranges",
+        "tags",
+        "maps",
+        "text",
+        "groups",
+        "controls",
+        "embedded",
+        "discriminators",
+    ] {
+        let opts = Options {
+            language: Language::Rust,
+            ..Options::default()
+        };
+        runner.pass(render_run(name, opts));
+    }
+
+    // A dictionary of C has a capacity, and a length that may exceed it
+    let opts = Options {
+        language: Language::C,
+        ..Options::default()
+    };
+    runner.pass(render_run("dictionaries", opts));
+}
+
+#[test]
+fn test_options_default() {
+    // Options serialized before alias was added render aliases as they were rendered before
+    let opts: Options = serde_json::from_str(r#"{"language":"rust","prefix":null}"#).unwrap();
+    assert_eq!(opts.alias, Alias::Typedef);
+    assert_eq!(
+        opts,
+        Options {
+            language: Language::Rust,
+            ..Options::default()
+        }
+    );
 }
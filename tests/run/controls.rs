@@ -0,0 +1,32 @@
+
+fn main() {
+    // A member with a .default control defaults to it's value
+    assert_eq!(Probe::default().level, 3);
+
+    // The flags of a .bits control round trip, and unknown bits are rejected
+    let flags = TcpFlags::SYN | TcpFlags::ACK;
+    let back: TcpFlags = minicbor::decode(&minicbor::to_vec(flags).unwrap()).unwrap();
+    assert_eq!(back, flags);
+    assert!(back.contains(TcpFlags::ACK) && !back.contains(TcpFlags::FIN));
+    assert!(minicbor::decode::<TcpFlags>(&minicbor::to_vec(8u8).unwrap()).is_err());
+
+    // A value past the bound of a .le control is rejected when decoded
+    let mut serial = [0; 8];
+    serial[..4].copy_from_slice(b"AB12");
+    let mut probe = Probe {
+        reading: 100,
+        serial: Serial(serial),
+        ..Probe::default()
+    };
+    let back: Probe = minicbor::decode(&minicbor::to_vec(&probe).unwrap()).unwrap();
+    assert_eq!(back.reading, 100);
+    probe.reading = 101;
+    assert!(minicbor::decode::<Probe>(&minicbor::to_vec(&probe).unwrap()).is_err());
+
+    // A string that does not match the pattern of a .regexp control is rejected when decoded
+    let back: Serial = minicbor::decode(&minicbor::to_vec("AB12").unwrap()).unwrap();
+    assert_eq!(back.0, serial);
+    for bad in ["ab12", "AB", "AB12X"] {
+        assert!(minicbor::decode::<Serial>(&minicbor::to_vec(bad).unwrap()).is_err());
+    }
+}
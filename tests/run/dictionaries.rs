@@ -0,0 +1,30 @@
+
+fn encoded(n: u8) -> Vec<u8> {
+    let mut e = minicbor::Encoder::new(Vec::new());
+    e.map(n as u64).unwrap();
+    for k in 0..n {
+        e.u8(k).unwrap().u16(k as u16 * 100).unwrap();
+    }
+    e.into_writer()
+}
+
+fn main() {
+    // A dictionary is read back up to it's capacity
+    let full: slots = minicbor::decode(&encoded(4)).unwrap();
+    assert_eq!(full.len, 4);
+    assert_eq!(full.keys, [0, 1, 2, 3]);
+    assert_eq!(full.values, [0, 100, 200, 300]);
+    assert_eq!(minicbor::to_vec(&full).unwrap(), encoded(4));
+
+    // More entries than the capacity, or fewer than the minimum, are rejected when decoded
+    assert!(minicbor::decode::<slots>(&encoded(5)).is_err());
+    assert!(minicbor::decode::<slots>(&encoded(0)).is_err());
+
+    // And when encoded
+    let mut over = full.clone();
+    over.len = 5;
+    assert!(minicbor::to_vec(&over).is_err());
+    let mut under = full;
+    under.len = 0;
+    assert!(minicbor::to_vec(&under).is_err());
+}
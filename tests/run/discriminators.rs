@@ -0,0 +1,44 @@
+
+fn config(version: u64, kind: &str, magic: &[u8]) -> Vec<u8> {
+    let mut e = minicbor::Encoder::new(Vec::new());
+    e.map(4).unwrap();
+    e.str("version").unwrap().u64(version).unwrap();
+    e.str("kind").unwrap().str(kind).unwrap();
+    e.str("magic").unwrap().bytes(magic).unwrap();
+    e.str("id").unwrap().u8(7).unwrap();
+    e.into_writer()
+}
+
+fn decode_err<T: for<'b> minicbor::Decode<'b, ()>>(bytes: &[u8]) -> String {
+    match minicbor::decode::<T>(bytes) {
+        Ok(_) => panic!("decoded an invalid message"),
+        Err(e) => e.to_string(),
+    }
+}
+
+fn main() {
+    // Literal members are not stored, and are always written
+    let ok = config(2, "config", b"MB");
+    assert_eq!(minicbor::to_vec(&Config { id: 7 }).unwrap(), ok);
+    let back: Config = minicbor::decode(&ok).unwrap();
+    assert_eq!(back.id, 7);
+
+    // A literal member of another value, or a missing one, is rejected
+    let err = decode_err::<Config>(&config(3, "config", b"MB"));
+    assert!(err.contains("unexpected version value"), "{}", err);
+    let err = decode_err::<Config>(&config(2, "status", b"MB"));
+    assert!(err.contains("unexpected kind value"), "{}", err);
+    let err = decode_err::<Config>(&config(2, "config", b"MC"));
+    assert!(err.contains("unexpected magic value"), "{}", err);
+    let mut e = minicbor::Encoder::new(Vec::new());
+    e.map(1).unwrap().str("id").unwrap().u8(7).unwrap();
+    let err = decode_err::<Config>(e.writer());
+    assert!(err.contains("missing version member"), "{}", err);
+
+    // The same holds for the entries of an array
+    let ok = [0x82, 1, 9];
+    assert_eq!(minicbor::to_vec(&Frame { seq: 9 }).unwrap(), ok);
+    assert_eq!(minicbor::decode::<Frame>(&ok).unwrap().seq, 9);
+    let err = decode_err::<Frame>(&[0x82, 2, 9]);
+    assert!(err.contains("unexpected version value"), "{}", err);
+}
@@ -0,0 +1,50 @@
+
+fn main() {
+    // A .cbor byte string holds the encoding of it's value
+    let point = Point { x: 1, y: -300 };
+    let mut envelope = Envelope::default();
+    envelope.payload.set_item(&point).unwrap();
+    assert_eq!(envelope.payload.bytes(), minicbor::to_vec(&point).unwrap());
+    let back = envelope.payload.item().unwrap();
+    assert_eq!((back.x, back.y), (1, -300));
+
+    // A tagged .cbor byte string is written with it's tag
+    let mut signed = EnvelopeSigned::default();
+    signed.set_item(&Point { x: 2, y: 3 }).unwrap();
+    envelope.signed = Some(signed);
+
+    // A .cborseq byte string holds the encodings of it's values one after the other, until it is
+    // full
+    for i in 0..4 {
+        envelope.trail.push(&Point { x: i, y: i }).unwrap();
+    }
+    assert_eq!(envelope.trail.bytes().len(), 12);
+    assert!(envelope.trail.push(&Point { x: 4, y: 4 }).is_err());
+    assert_eq!(envelope.trail.bytes().len(), 12);
+    let items = envelope.trail.items().collect::<Result<Vec<_>, _>>().unwrap();
+    assert_eq!(items.iter().map(|p| (p.x, p.y)).collect::<Vec<_>>(), [(0, 0), (1, 1), (2, 2), (3, 3)]);
+
+    // The envelope round trips with the values it embeds
+    let cbor = minicbor::to_vec(&envelope).unwrap();
+    assert_eq!(minicbor::len(&envelope), cbor.len());
+    let back: Envelope = minicbor::decode(&cbor).unwrap();
+    assert_eq!(back.payload.bytes(), envelope.payload.bytes());
+    let signed = back.signed.as_ref().unwrap().item().unwrap();
+    assert_eq!((signed.x, signed.y), (2, 3));
+    assert_eq!(back.trail.items().count(), 4);
+    assert_eq!(minicbor::to_vec(&back).unwrap(), cbor);
+
+    // A byte string past it's capacity is rejected, and so is a tagged value without it's tag
+    let mut e = minicbor::Encoder::new(Vec::new());
+    e.map(2).unwrap();
+    e.str("payload").unwrap().bytes(&[0; 17]).unwrap();
+    e.str("trail").unwrap().bytes(&[]).unwrap();
+    assert!(minicbor::decode::<Envelope>(e.writer()).is_err());
+    let mut e = minicbor::Encoder::new(Vec::new());
+    e.bytes(envelope.payload.bytes()).unwrap();
+    assert!(minicbor::decode::<EnvelopeSigned>(e.writer()).is_err());
+    let mut e = minicbor::Encoder::new(Vec::new());
+    e.tag(minicbor::data::Tag::Unassigned(24)).unwrap();
+    e.bytes(envelope.payload.bytes()).unwrap();
+    assert!(minicbor::decode::<EnvelopeSigned>(e.writer()).is_ok());
+}
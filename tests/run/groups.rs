@@ -0,0 +1,14 @@
+
+fn main() {
+    // A group that is the type of a member is encoded like a map of it's own
+    let host = Host { addr: Ip { a: 1, b: 2 }, backup: None };
+    let mut e = minicbor::Encoder::new(Vec::new());
+    e.map(1).unwrap().str("addr").unwrap().map(2).unwrap();
+    e.str("a").unwrap().u8(1).unwrap();
+    e.str("b").unwrap().u8(2).unwrap();
+    let bytes = e.into_writer();
+    assert_eq!(minicbor::to_vec(&host).unwrap(), bytes);
+    let back: Host = minicbor::decode(&bytes).unwrap();
+    assert_eq!((back.addr.a, back.addr.b), (1, 2));
+    assert!(back.backup.is_none());
+}
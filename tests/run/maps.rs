@@ -0,0 +1,29 @@
+
+fn main() {
+    // A map is keyed by member name, and an absent optional member is not written
+    let device = Device { id: 1, port: 80, alias: None };
+    let mut e = minicbor::Encoder::new(Vec::new());
+    e.map(2).unwrap();
+    e.str("id").unwrap().u8(1).unwrap();
+    e.str("port").unwrap().u16(80).unwrap();
+    assert_eq!(minicbor::to_vec(&device).unwrap(), e.into_writer());
+
+    // Members may be in any order, and members of a newer revision are skipped
+    let mut e = minicbor::Encoder::new(Vec::new());
+    e.map(5).unwrap();
+    e.str("port").unwrap().u16(443).unwrap();
+    e.str("vendor").unwrap().str("acme").unwrap();
+    e.u8(9).unwrap().array(2).unwrap().u8(1).unwrap().u8(2).unwrap();
+    e.str("alias").unwrap().u16(8443).unwrap();
+    e.str("id").unwrap().u8(2).unwrap();
+    let back: Device = minicbor::decode(e.writer()).unwrap();
+    assert_eq!((back.id, back.port, back.alias), (2, 443, Some(8443)));
+
+    // A missing member is rejected, and so is an array
+    let mut e = minicbor::Encoder::new(Vec::new());
+    e.map(1).unwrap().str("id").unwrap().u8(1).unwrap();
+    assert!(minicbor::decode::<Device>(e.writer()).is_err());
+    let mut e = minicbor::Encoder::new(Vec::new());
+    e.array(2).unwrap().u8(1).unwrap().u16(80).unwrap();
+    assert!(minicbor::decode::<Device>(e.writer()).is_err());
+}
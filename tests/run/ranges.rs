@@ -0,0 +1,21 @@
+fn encode<'a>(reading: &Reading, buf: &'a mut [u8]) -> &'a [u8] {
+    let cap = buf.len();
+    let mut w = &mut buf[..];
+    minicbor::encode(reading, &mut w).unwrap();
+    let len = cap - w.len();
+    &buf[..len]
+}
+
+fn main() {
+    // The bounds of a range are inclusive
+    let mut buf = [0; 64];
+    let ok = Reading { level: 10, offset: -5, percent: 100 };
+    let back: Reading = minicbor::decode(encode(&ok, &mut buf)).unwrap();
+    assert_eq!((back.level, back.offset, back.percent), (10, -5, 100));
+
+    // A value outside of a range is encoded as is, but rejected when decoded
+    for (level, offset, percent) in [(0, 0, 0), (11, 0, 0), (1, -6, 0), (1, 6, 0), (1, 0, 101)] {
+        let bad = encode(&Reading { level, offset, percent }, &mut buf);
+        assert!(minicbor::decode::<Reading>(bad).is_err());
+    }
+}
@@ -0,0 +1,71 @@
+use minicbor::data::{Tag, Type};
+use minicbor::{Decoder, Encoder};
+
+/// Copies a CBOR item, replacing each tag with the result of `f`. A tag is dropped when `f`
+/// returns None
+fn retag(d: &mut Decoder, e: &mut Encoder<Vec<u8>>, f: &impl Fn(Tag) -> Option<Tag>) {
+    match d.datatype().unwrap() {
+        Type::Tag => {
+            if let Some(tag) = f(d.tag().unwrap()) {
+                e.tag(tag).unwrap();
+            }
+            retag(d, e, f);
+        }
+        Type::Array => {
+            let len = d.array().unwrap().unwrap();
+            e.array(len).unwrap();
+            (0..len).for_each(|_| retag(d, e, f));
+        }
+        Type::Map => {
+            let len = d.map().unwrap().unwrap();
+            e.map(len).unwrap();
+            (0..len * 2).for_each(|_| retag(d, e, f));
+        }
+        Type::String => {
+            e.str(d.str().unwrap()).unwrap();
+        }
+        Type::Null => {
+            d.null().unwrap();
+            e.null().unwrap();
+        }
+        _ => {
+            e.u64(d.u64().unwrap()).unwrap();
+        }
+    }
+}
+
+fn with_tags(cbor: &[u8], f: impl Fn(Tag) -> Option<Tag>) -> Vec<u8> {
+    let mut e = Encoder::new(Vec::new());
+    retag(&mut Decoder::new(cbor), &mut e, &f);
+    e.into_writer()
+}
+
+fn main() {
+    // Every tag is written, and read back
+    let event = Event {
+        at: 1,
+        origin: Some(Node { id: 2 }),
+        stamps: [3, 4],
+    };
+    let ok = minicbor::to_vec(&event).unwrap();
+    let tags = std::cell::RefCell::new(Vec::new());
+    let copy = with_tags(&ok, |tag| {
+        tags.borrow_mut().push(tag);
+        Some(tag)
+    });
+    assert_eq!(copy, ok);
+    let (timestamp, unassigned) = (Tag::Timestamp, Tag::Unassigned(40000));
+    assert_eq!(tags.into_inner(), [timestamp, unassigned, timestamp, timestamp]);
+    let back: Event = minicbor::decode(&ok).unwrap();
+    assert_eq!(back.at, 1);
+    assert_eq!(back.origin.as_ref().map(|node| node.id), Some(2));
+    assert_eq!(back.stamps, [3, 4]);
+
+    // A value without it's tag, or with the wrong tag, is rejected
+    for (tag, other) in [(timestamp, Tag::DateTime), (unassigned, Tag::Unassigned(40001))] {
+        let missing = with_tags(&ok, |t| (t != tag).then_some(t));
+        assert!(minicbor::decode::<Event>(&missing).is_err());
+        let wrong = with_tags(&ok, |t| Some(if t == tag { other } else { t }));
+        assert!(minicbor::decode::<Event>(&wrong).is_err());
+    }
+}
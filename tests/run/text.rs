@@ -0,0 +1,52 @@
+
+fn with_name(name: impl FnOnce(&mut minicbor::Encoder<Vec<u8>>)) -> Vec<u8> {
+    let mut e = minicbor::Encoder::new(Vec::new());
+    e.map(2).unwrap();
+    e.str("name").unwrap();
+    name(&mut e);
+    e.str("tags").unwrap().array(2).unwrap();
+    e.str("a").unwrap().str("b").unwrap();
+    e.into_writer()
+}
+
+fn main() {
+    // A fixed size string is written as a text string, without it's padding
+    let mut name = [0; 8];
+    name[..6].copy_from_slice("héllo".as_bytes());
+    let label = Label { name, note: Some(*b"ab\0\0"), tags: [*b"a\0\0\0", *b"b\0\0\0"] };
+    let cbor = minicbor::to_vec(&label).unwrap();
+    let mut d = minicbor::Decoder::new(&cbor);
+    assert_eq!(d.map().unwrap(), Some(3));
+    for key in ["name", "note", "tags"] {
+        assert_eq!(d.str().unwrap(), key);
+        if key == "tags" {
+            assert_eq!(d.array().unwrap(), Some(2));
+            assert_eq!(d.str().unwrap(), "a");
+            assert_eq!(d.str().unwrap(), "b");
+        } else {
+            assert_eq!(d.datatype().unwrap(), minicbor::data::Type::String);
+            d.skip().unwrap();
+        }
+    }
+
+    // UTF-8 is read back as it was written
+    let back: Label = minicbor::decode(&cbor).unwrap();
+    assert_eq!(back.name, name);
+    assert_eq!(back.note, Some(*b"ab\0\0"));
+    assert_eq!(back.tags, [*b"a\0\0\0", *b"b\0\0\0"]);
+    assert_eq!(minicbor::to_vec(&back).unwrap(), cbor);
+
+    // A string that does not fit is rejected, and so is a byte string
+    let ok = with_name(|e| {
+        e.str("héllo").unwrap();
+    });
+    assert!(minicbor::decode::<Label>(&ok).is_ok());
+    let long = with_name(|e| {
+        e.str("abcdefghi").unwrap();
+    });
+    assert!(minicbor::decode::<Label>(&long).is_err());
+    let bytes = with_name(|e| {
+        e.bytes(b"abc").unwrap();
+    });
+    assert!(minicbor::decode::<Label>(&bytes).is_err());
+}